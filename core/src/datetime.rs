@@ -1,7 +1,10 @@
 use std::error::Error;
+use std::sync::OnceLock;
 
+use chrono::format::{Item, StrftimeItems};
 use chrono::{
-    Datelike, Days, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike,
+    Datelike, Days, FixedOffset, Local, LocalResult, Months, NaiveDate, NaiveDateTime, NaiveTime,
+    Offset, TimeDelta, TimeZone, Timelike, Utc, Weekday,
 };
 use regex::Regex;
 
@@ -9,14 +12,125 @@ const DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
 const DATE_FORMAT: &str = "%Y-%m-%d";
 const TIME_FORMAT: &str = "%H:%M";
 
+/// Fixed UTC offset used instead of the system local time zone.
+static FIXED_OFFSET: OnceLock<FixedOffset> = OnceLock::new();
+
+/// Pins the time zone used to get the current time to a fixed UTC offset.
+///
+/// Stored times have no time zone information, and they are interpreted as wall-clock times
+/// of the effective time zone. The system local time zone is used unless an offset is pinned,
+/// so a traveler can pin their home zone to keep working date boundaries consistent.
+/// The offset can be set only once.
+pub fn set_fixed_offset(offset: FixedOffset) -> Result<(), Box<dyn Error>> {
+    FIXED_OFFSET
+        .set(offset)
+        .map_err(|_| "time zone offset is already set".into())
+}
+
+/// Tries to parse given UTC offset string (`+HH:MM` or `+HHMM`) to `FixedOffset`.
+pub fn parse_utc_offset(s: &str) -> Result<FixedOffset, Box<dyn Error>> {
+    let offset_re = Regex::new(r"^[+-][0-9]{2}:?[0-9]{2}$").unwrap();
+    if !offset_re.is_match(s) {
+        return Err(ParseError::new("invalid format").into());
+    }
+    Ok(s.parse()?)
+}
+
+/// Display formats of dates and times (`strftime` syntax).
+static DATE_DISPLAY_FORMAT: OnceLock<String> = OnceLock::new();
+static TIME_DISPLAY_FORMAT: OnceLock<String> = OnceLock::new();
+
+/// Sets the formats used by `to_string_display` (e.g. `%m/%d` and `%-I:%M %p`).
+///
+/// Only the output for display is affected; `Display` and `to_string_hm` keep the fixed
+/// formats so that their output can be parsed back. The formats can be set only once.
+pub fn set_display_format(date: Option<&str>, time: Option<&str>) -> Result<(), Box<dyn Error>> {
+    if let Some(date) = date {
+        check_format(date)?;
+        DATE_DISPLAY_FORMAT
+            .set(date.to_string())
+            .map_err(|_| "date display format is already set")?;
+    }
+    if let Some(time) = time {
+        check_format(time)?;
+        TIME_DISPLAY_FORMAT
+            .set(time.to_string())
+            .map_err(|_| "time display format is already set")?;
+    }
+    Ok(())
+}
+
+/// Checks whether a `strftime` format string is valid.
+fn check_format(fmt: &str) -> Result<(), Box<dyn Error>> {
+    if StrftimeItems::new(fmt).any(|item| item == Item::Error) {
+        return Err(ParseError::new(&format!("invalid format: {}", fmt)).into());
+    }
+    Ok(())
+}
+
+/// Current date and time in the effective time zone, with full precision.
+pub fn now_exact() -> NaiveDateTime {
+    now_naive()
+}
+
+/// Current date and time in the effective time zone.
+fn now_naive() -> NaiveDateTime {
+    match FIXED_OFFSET.get() {
+        Some(offset) => Utc::now().with_timezone(offset).naive_local(),
+        None => Local::now().naive_local(),
+    }
+}
+
+/// Converts a wall-clock time in a time zone to UTC.
+///
+/// Ambiguous times in a fold take the earlier instant, and nonexistent times in a gap are
+/// interpreted with the offset before the transition, i.e. moved forward by the gap.
+fn to_utc<Tz: TimeZone>(naive: &NaiveDateTime, tz: &Tz) -> NaiveDateTime {
+    let offset = match tz.offset_from_local_datetime(naive) {
+        LocalResult::Single(offset) | LocalResult::Ambiguous(offset, _) => offset.fix(),
+        LocalResult::None => {
+            // transitions do not happen twice a day
+            let before = *naive - TimeDelta::days(1);
+            tz.offset_from_local_datetime(&before)
+                .earliest()
+                .unwrap()
+                .fix()
+        }
+    };
+    *naive - offset
+}
+
+/// Error on parsing date/time strings.
+#[derive(Debug)]
+pub struct ParseError(String);
+
+impl ParseError {
+    fn new(message: &str) -> Self {
+        ParseError(message.to_string())
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for ParseError {}
+
 /// Time format
 pub trait TimeDisplay {
     /// Convert datetime/time to `String`, its format is `HH:MM`.
     fn to_string_hm(&self) -> String;
+
+    /// Convert datetime/time to `String` for display. Defaults to `to_string_hm`.
+    fn to_string_display(&self) -> String {
+        self.to_string_hm()
+    }
 }
 
 /// Represents time.
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
 pub struct TaskTime(NaiveDateTime);
 
 impl From<NaiveDateTime> for TaskTime {
@@ -55,10 +169,22 @@ impl std::ops::Sub for &TaskTime {
     }
 }
 
+impl std::str::FromStr for TaskTime {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s).map_err(|e| ParseError(e.to_string()))
+    }
+}
+
 impl TimeDisplay for TaskTime {
     fn to_string_hm(&self) -> String {
         self.0.format(TIME_FORMAT).to_string()
     }
+
+    fn to_string_display(&self) -> String {
+        self.format(TIME_DISPLAY_FORMAT.get().map_or(TIME_FORMAT, |s| s))
+    }
 }
 
 impl TaskTime {
@@ -72,7 +198,7 @@ impl TaskTime {
     /// The expected format is `HH:MM` or `HHMM`.
     pub fn parse_hm(s: &str) -> Result<Self, Box<dyn Error>> {
         let (h, m) = parse_time_hm(s)?;
-        let today = Local::now().date_naive();
+        let today = now_naive().date();
         let time = today.and_hms_opt(h, m, 0).unwrap();
         Ok(time.into())
     }
@@ -90,10 +216,59 @@ impl TaskTime {
         Ok(date.0.and_hms_opt(h, m, 0).unwrap().into())
     }
 
+    /// Tries to build a `TaskTime` from a `WorkingDate` and a time string.
+    ///
+    /// Besides `HH:MM`/`HHMM` as in `parse_with_date`, a date-qualified time
+    /// (`[YYYY-]MM-DD HH:MM`) is accepted and taken as is, regardless of the working date.
+    pub fn parse_with_date_or_datetime(
+        date: &WorkingDate,
+        s: &str,
+    ) -> Result<Self, Box<dyn Error>> {
+        match s.trim().split_once(' ') {
+            Some((date_part, time_part)) => {
+                let (y, m, d) = parse_date(date_part)?;
+                let (h, min) = parse_time_hm(time_part.trim())?;
+                let datetime = NaiveDate::from_ymd_opt(y, m, d)
+                    .ok_or_else(|| ParseError::new("invalid date"))?
+                    .and_hms_opt(h, min, 0)
+                    .unwrap();
+                Ok(datetime.into())
+            }
+            None => Self::parse_with_date(date, s),
+        }
+    }
+
+    /// Actual elapsed time from `self` to `end` in a time zone, accounting for DST transitions.
+    ///
+    /// Subtracting `TaskTime`s gives the difference of wall-clock times, which is the default
+    /// for durations. This gives one hour less or more across a DST transition.
+    pub fn elapsed_in<Tz: TimeZone>(&self, end: &TaskTime, tz: &Tz) -> TimeDelta {
+        to_utc(&end.0, tz) - to_utc(&self.0, tz)
+    }
+
+    /// Actual elapsed time from `self` to `end` in the effective time zone.
+    ///
+    /// This is the same as the wall-clock difference if a fixed UTC offset is pinned.
+    pub fn elapsed_until(&self, end: &TaskTime) -> TimeDelta {
+        match FIXED_OFFSET.get() {
+            Some(offset) => self.elapsed_in(end, offset),
+            None => self.elapsed_in(end, &Local),
+        }
+    }
+
+    /// Formats the time with a `strftime` format string.
+    pub fn format(&self, fmt: &str) -> String {
+        self.0.format(fmt).to_string()
+    }
+
+    /// Returns the time moved by `delta`.
+    pub fn shift(&self, delta: TimeDelta) -> TaskTime {
+        (self.0 + delta).into()
+    }
+
     /// Current time.
     pub fn now() -> Self {
-        let now = Local::now().naive_local();
-        now.into()
+        now_naive().into()
     }
 }
 
@@ -110,7 +285,7 @@ impl TimeDisplay for TimeDelta {
 /// Represents a date.
 ///
 /// In `WorkingDate`, after 5:00 am is considered as the next date.
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
 pub struct WorkingDate(NaiveDate);
 
 impl From<NaiveDate> for WorkingDate {
@@ -145,11 +320,20 @@ impl std::fmt::Display for WorkingDate {
     }
 }
 
+impl std::str::FromStr for WorkingDate {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s).map_err(|e| ParseError(e.to_string()))
+    }
+}
+
 impl WorkingDate {
     /// Tries to parse given string to `WorkingDate`. The expected format is `YYYY-MM-DD`.
     pub fn parse(s: &str) -> Result<Self, Box<dyn Error>> {
         let (y, m, d) = parse_date(s)?;
-        let date = NaiveDate::from_ymd_opt(y, m, d).ok_or("invalid date")?;
+        let date =
+            NaiveDate::from_ymd_opt(y, m, d).ok_or_else(|| ParseError::new("invalid date"))?;
         Ok(date.into())
     }
 
@@ -158,12 +342,43 @@ impl WorkingDate {
     /// Start is the first day of the month, and end is the last day of the month.
     pub fn parse_ym(s: &str) -> Result<(Self, Self), Box<dyn Error>> {
         let (y, m) = parse_yearmonth(s)?;
-        let date_first = NaiveDate::from_ymd_opt(y, m, 1).unwrap();
-        let date_last = NaiveDate::from_ymd_opt(y, m, 1)
-            .and_then(|d| d.checked_add_months(Months::new(1)))
+        Ok(Self::month_bounds(y, m))
+    }
+
+    /// First and last days of the month.
+    ///
+    /// Panics if `month` is not in `1..=12`.
+    pub fn month_bounds(year: i32, month: u32) -> (Self, Self) {
+        let date_first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+        let date_last = date_first
+            .checked_add_months(Months::new(1))
             .and_then(|d| d.pred_opt())
             .unwrap();
 
+        (date_first.into(), date_last.into())
+    }
+
+    /// First and last days of the month containing the date.
+    pub fn containing_month(&self) -> (Self, Self) {
+        Self::month_bounds(self.0.year(), self.0.month())
+    }
+
+    /// First day of the year containing the date.
+    pub fn year_start(&self) -> Self {
+        NaiveDate::from_ymd_opt(self.0.year(), 1, 1).unwrap().into()
+    }
+
+    /// Tries to parse given ISO 8601 week string (`YYYY-Www` or `YYYYWww`) to (start, end) tuple.
+    ///
+    /// Start is the Monday of the week, and end is the Sunday of the week.
+    /// Either of them can fall in the adjacent year (e.g. `2020-W53` ends on 2021-01-03).
+    pub fn parse_isoweek(s: &str) -> Result<(Self, Self), Box<dyn Error>> {
+        let (y, w) = parse_isoweek(s)?;
+        let date_first = NaiveDate::from_isoywd_opt(y, w, Weekday::Mon)
+            .ok_or_else(|| ParseError::new("invalid week"))?;
+        let date_last = NaiveDate::from_isoywd_opt(y, w, Weekday::Sun)
+            .ok_or_else(|| ParseError::new("invalid week"))?;
+
         Ok((date_first.into(), date_last.into()))
     }
 
@@ -182,16 +397,143 @@ impl WorkingDate {
         }
     }
 
+    /// End of the working date, which is 5:00 am of the next day.
+    pub fn end_of_day(&self) -> TaskTime {
+        let date = self.0.checked_add_days(Days::new(1)).unwrap();
+        date.and_hms_opt(5, 0, 0).unwrap().into()
+    }
+
+    /// Number of days from `earlier` to this date.
+    pub fn days_since(&self, earlier: &WorkingDate) -> i64 {
+        (self.0 - earlier.0).num_days()
+    }
+
+    /// Tries to parse a time window (`HH:MM-HH:MM`) on the date to (begin, end) tuple.
+    ///
+    /// Times before 5:00 am fall on the next day as in `TaskTime::parse_with_date`.
+    pub fn parse_window(&self, s: &str) -> Result<(TaskTime, TaskTime), Box<dyn Error>> {
+        let (begin, end) = s
+            .split_once('-')
+            .ok_or_else(|| ParseError::new("invalid format"))?;
+        let begin = TaskTime::parse_with_date(self, begin.trim())?;
+        let end = TaskTime::parse_with_date(self, end.trim())?;
+        if end <= begin {
+            return Err("end of the window must be later than the beginning".into());
+        }
+        Ok((begin, end))
+    }
+
+    /// Iterates over dates from this date through `last`, both inclusive.
+    pub fn iter_days(&self, last: &WorkingDate) -> impl Iterator<Item = WorkingDate> {
+        let last = last.0;
+        self.0
+            .iter_days()
+            .take_while(move |date| date <= &last)
+            .map(WorkingDate)
+    }
+
+    /// Day of the week.
+    pub fn weekday(&self) -> Weekday {
+        self.0.weekday()
+    }
+
+    /// Formats the date with a `strftime` format string.
+    pub fn format(&self, fmt: &str) -> String {
+        self.0.format(fmt).to_string()
+    }
+
+    /// Convert date to `String` for display.
+    pub fn to_string_display(&self) -> String {
+        self.format(DATE_DISPLAY_FORMAT.get().map_or(DATE_FORMAT, |s| s))
+    }
+
     /// Current date
     pub fn today() -> Self {
         TaskTime::now().into()
     }
 }
 
+/// Tries to parse given duration string (`H:MM`, or e.g. `90m`, `1h30m`, `2h`) to `TimeDelta`.
+///
+/// Durations are never negative; use `parse_shift` for signed offsets.
+pub fn parse_duration(s: &str) -> Result<TimeDelta, Box<dyn Error>> {
+    if s.starts_with('-') {
+        return Err(ParseError::new("negative duration").into());
+    }
+    let duration_re = Regex::new(r"^([0-9]+):([0-5][0-9])$").unwrap();
+    let Some(captures) = duration_re.captures(s) else {
+        return parse_duration_units(s);
+    };
+
+    let h = captures.get(1).unwrap().as_str().parse()?;
+    let m = captures.get(2).unwrap().as_str().parse()?;
+    duration_from_hm(h, m)
+}
+
+/// Makes a duration of hours and minutes, failing instead of overflowing.
+fn duration_from_hm(h: i64, m: i64) -> Result<TimeDelta, Box<dyn Error>> {
+    TimeDelta::try_hours(h)
+        .zip(TimeDelta::try_minutes(m))
+        .and_then(|(h, m)| h.checked_add(&m))
+        .ok_or_else(|| ParseError::new("duration out of range").into())
+}
+
+/// Weekend days unless configured otherwise.
+pub const DEFAULT_WEEKEND: [Weekday; 2] = [Weekday::Sat, Weekday::Sun];
+
+/// Tries to parse comma-separated days of the week (e.g. `sat,sun`, `Fri, Sat`).
+///
+/// An empty string gives no days, for those who work every day.
+pub fn parse_weekdays(s: &str) -> Result<Vec<Weekday>, Box<dyn Error>> {
+    let mut days = vec![];
+    for day in s.split(',').map(str::trim).filter(|day| !day.is_empty()) {
+        let day = day
+            .parse::<Weekday>()
+            .map_err(|_| ParseError::new(&format!("invalid day of the week: {}", day)))?;
+        if !days.contains(&day) {
+            days.push(day);
+        }
+    }
+    Ok(days)
+}
+
+/// Checks whether the date is expected to be worked, i.e. neither on `weekend` nor a holiday.
+pub fn is_working_day(date: &WorkingDate, weekend: &[Weekday], holidays: &[WorkingDate]) -> bool {
+    !weekend.contains(&date.weekday()) && !holidays.contains(date)
+}
+
+/// Parse duration string with units (e.g. `90m`, `1h30m`, `2h`) to `TimeDelta`.
+fn parse_duration_units(s: &str) -> Result<TimeDelta, Box<dyn Error>> {
+    let duration_re = Regex::new(r"^(?:([0-9]+)h)?(?:([0-9]+)m)?$").unwrap();
+    let captures = duration_re
+        .captures(s)
+        .ok_or_else(|| ParseError::new("invalid format"))?;
+    if captures.get(1).is_none() && captures.get(2).is_none() {
+        return Err(ParseError::new("invalid format").into());
+    }
+
+    let h = captures.get(1).map_or(Ok(0), |h| h.as_str().parse())?;
+    let m = captures.get(2).map_or(Ok(0), |m| m.as_str().parse())?;
+    duration_from_hm(h, m)
+}
+
+/// Tries to parse given signed offset string (e.g. `+30m`, `-1h15m`, `+2h`) to `TimeDelta`.
+pub fn parse_shift(s: &str) -> Result<TimeDelta, Box<dyn Error>> {
+    if let Some(duration) = s.strip_prefix('+') {
+        parse_duration_units(duration)
+    } else if let Some(duration) = s.strip_prefix('-') {
+        parse_duration_units(duration).map(|delta| -delta)
+    } else {
+        Err(ParseError::new("invalid format").into())
+    }
+}
+
 /// Parse time string (`HH:MM`, `H:MM`, `HHMM`, or `HMM`) to (hour, minutes) tuple.
 fn parse_time_hm(s: &str) -> Result<(u32, u32), Box<dyn Error>> {
     let time_re = Regex::new(r"^([0-9]|[01][0-9]|2[0-3]):?([0-5][0-9])$").unwrap();
-    let captures = time_re.captures(s).ok_or("invalid format")?;
+    let captures = time_re
+        .captures(s)
+        .ok_or_else(|| ParseError::new("invalid format"))?;
 
     let h = captures.get(1).unwrap().as_str().parse()?;
     let m = captures.get(2).unwrap().as_str().parse()?;
@@ -203,12 +545,14 @@ fn parse_time_hm(s: &str) -> Result<(u32, u32), Box<dyn Error>> {
 fn parse_date(s: &str) -> Result<(i32, u32, u32), Box<dyn Error>> {
     let date_re =
         Regex::new(r"^(([0-9]{4})-?)?(0[1-9]|1[0-2])-?(0[1-9]|[12][0-9]|3[01])$").unwrap();
-    let captures = date_re.captures(s).ok_or("invalid format")?;
+    let captures = date_re
+        .captures(s)
+        .ok_or_else(|| ParseError::new("invalid format"))?;
 
     let y = if let Some(matched) = captures.get(2) {
         matched.as_str().parse()?
     } else {
-        Local::now().year()
+        now_naive().year()
     };
     let m = captures.get(3).unwrap().as_str().parse()?;
     let d = captures.get(4).unwrap().as_str().parse()?;
@@ -219,7 +563,9 @@ fn parse_date(s: &str) -> Result<(i32, u32, u32), Box<dyn Error>> {
 /// Parse year-month string (`YYYY-MM` or `YYYYMM`) to (year, month) tuple.
 fn parse_yearmonth(s: &str) -> Result<(i32, u32), Box<dyn Error>> {
     let ym_re = Regex::new(r"^([0-9]{4})-?(0[1-9]|1[0-2])$").unwrap();
-    let captures = ym_re.captures(s).ok_or("invalid format")?;
+    let captures = ym_re
+        .captures(s)
+        .ok_or_else(|| ParseError::new("invalid format"))?;
 
     let y = captures.get(1).unwrap().as_str().parse()?;
     let m = captures.get(2).unwrap().as_str().parse()?;
@@ -227,6 +573,19 @@ fn parse_yearmonth(s: &str) -> Result<(i32, u32), Box<dyn Error>> {
     Ok((y, m))
 }
 
+/// Parse ISO week string (`YYYY-Www` or `YYYYWww`) to (year, week) tuple.
+fn parse_isoweek(s: &str) -> Result<(i32, u32), Box<dyn Error>> {
+    let week_re = Regex::new(r"^([0-9]{4})-?W(0[1-9]|[1-4][0-9]|5[0-3])$").unwrap();
+    let captures = week_re
+        .captures(s)
+        .ok_or_else(|| ParseError::new("invalid format"))?;
+
+    let y = captures.get(1).unwrap().as_str().parse()?;
+    let w = captures.get(2).unwrap().as_str().parse()?;
+
+    Ok((y, w))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -240,6 +599,14 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_tasktime_from_str() {
+        let result = "2022-06-30T11:30:25".parse::<TaskTime>().unwrap();
+        assert_eq!(result, TaskTime::parse("2022-06-30T11:30:00").unwrap());
+
+        assert!("2022-06-30".parse::<TaskTime>().is_err());
+    }
+
     #[test]
     fn test_tasktime_parse_with_date() {
         let date = WorkingDate::parse("2021-01-01").unwrap();
@@ -268,6 +635,131 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_tasktime_parse_with_date_or_datetime() {
+        let date = WorkingDate::parse("2021-01-04").unwrap();
+        let result = TaskTime::parse_with_date_or_datetime(&date, "2021-01-06 08:30").unwrap();
+        let expected = TaskTime::parse("2021-01-06T08:30:00").unwrap();
+        assert_eq!(result, expected);
+
+        // no rollover for date-qualified times
+        let result = TaskTime::parse_with_date_or_datetime(&date, "20210106 0300").unwrap();
+        let expected = TaskTime::parse("2021-01-06T03:00:00").unwrap();
+        assert_eq!(result, expected);
+
+        let result = TaskTime::parse_with_date_or_datetime(&date, "0300").unwrap();
+        let expected = TaskTime::parse("2021-01-05T03:00:00").unwrap();
+        assert_eq!(result, expected);
+
+        assert!(TaskTime::parse_with_date_or_datetime(&date, "2021-02-30 08:30").is_err());
+        assert!(TaskTime::parse_with_date_or_datetime(&date, "2021-01-06 25:00").is_err());
+    }
+
+    #[test]
+    fn test_tasktime_format() {
+        let noon = TaskTime::parse("2021-01-01T12:00:00").unwrap();
+        let midnight = TaskTime::parse("2021-01-02T00:00:00").unwrap();
+        let evening = TaskTime::parse("2021-01-01T18:05:00").unwrap();
+        assert_eq!(noon.format("%-I:%M %p"), "12:00 PM");
+        assert_eq!(midnight.format("%-I:%M %p"), "12:00 AM");
+        assert_eq!(evening.format("%-I:%M %p"), "6:05 PM");
+    }
+
+    #[test]
+    fn test_workingdate_format() {
+        let date = WorkingDate::parse("2021-03-04").unwrap();
+        assert_eq!(date.format("%m/%d"), "03/04");
+    }
+
+    #[test]
+    fn test_check_format() {
+        assert!(check_format("%-I:%M %p").is_ok());
+        assert!(check_format("%m/%d").is_ok());
+        assert!(check_format("%H:%").is_err());
+    }
+
+    /// US Eastern time in 2021: DST from 03-14 02:00 to 11-07 02:00 local time.
+    #[derive(Clone)]
+    struct Eastern2021;
+
+    impl Eastern2021 {
+        fn is_dst_utc(utc: &NaiveDateTime) -> bool {
+            let start = TaskTime::parse("2021-03-14T07:00:00").unwrap().0;
+            let end = TaskTime::parse("2021-11-07T06:00:00").unwrap().0;
+            start <= *utc && *utc < end
+        }
+
+        fn offset(dst: bool) -> FixedOffset {
+            FixedOffset::west_opt(if dst { 4 * 3600 } else { 5 * 3600 }).unwrap()
+        }
+    }
+
+    impl TimeZone for Eastern2021 {
+        type Offset = FixedOffset;
+
+        fn from_offset(_offset: &FixedOffset) -> Self {
+            Eastern2021
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(12, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let candidates = [Self::offset(false), Self::offset(true)]
+                .into_iter()
+                .filter(|offset| {
+                    let utc = *local - *offset;
+                    Self::offset(Self::is_dst_utc(&utc)) == *offset
+                })
+                .collect::<Vec<_>>();
+            match candidates[..] {
+                [offset] => LocalResult::Single(offset),
+                // standard time is later than daylight saving time in a fold
+                [standard, dst] => LocalResult::Ambiguous(dst, standard),
+                _ => LocalResult::None,
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(12, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            Self::offset(Self::is_dst_utc(utc))
+        }
+    }
+
+    #[test]
+    fn test_tasktime_elapsed_in() {
+        let time = |s: &str| TaskTime::parse(s).unwrap();
+
+        // spring forward
+        let begin = time("2021-03-14T01:00:00");
+        let end = time("2021-03-14T04:00:00");
+        assert_eq!(&end - &begin, TimeDelta::hours(3));
+        assert_eq!(begin.elapsed_in(&end, &Eastern2021), TimeDelta::hours(2));
+
+        // nonexistent time is moved forward by the gap
+        let gap = time("2021-03-14T02:30:00");
+        assert_eq!(begin.elapsed_in(&gap, &Eastern2021), TimeDelta::minutes(90));
+
+        // fall back, ambiguous 01:30 takes the earlier instant
+        let begin = time("2021-11-07T00:00:00");
+        let end = time("2021-11-07T03:00:00");
+        assert_eq!(begin.elapsed_in(&end, &Eastern2021), TimeDelta::hours(4));
+        let fold = time("2021-11-07T01:30:00");
+        assert_eq!(
+            begin.elapsed_in(&fold, &Eastern2021),
+            TimeDelta::minutes(90)
+        );
+
+        // no transition
+        let begin = time("2021-06-01T09:00:00");
+        let end = time("2021-06-01T17:30:00");
+        assert_eq!(begin.elapsed_in(&end, &Eastern2021), &end - &begin);
+    }
+
     #[test]
     fn test_tasktime_to_string() {
         let t_str = "2022-06-30T11:30:25";
@@ -301,6 +793,15 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_workingdate_from_str() {
+        let result = "2021-01-01".parse::<WorkingDate>().unwrap();
+        let expected = WorkingDate(NaiveDate::from_ymd_opt(2021, 1, 1).unwrap());
+        assert_eq!(result, expected);
+
+        assert!("2021-02-30".parse::<WorkingDate>().is_err());
+    }
+
     #[test]
     fn test_workingdate_parse_md() {
         let this_year = Local::now().year();
@@ -327,6 +828,67 @@ mod tests {
         assert_eq!(en, en_expected);
     }
 
+    #[test]
+    fn test_workingdate_month_bounds() {
+        // the next month is in the next year
+        let (st, en) = WorkingDate::month_bounds(2021, 12);
+        assert_eq!(st, WorkingDate::parse("2021-12-01").unwrap());
+        assert_eq!(en, WorkingDate::parse("2021-12-31").unwrap());
+
+        let (st, en) = WorkingDate::month_bounds(2022, 1);
+        assert_eq!(st, WorkingDate::parse("2022-01-01").unwrap());
+        assert_eq!(en, WorkingDate::parse("2022-01-31").unwrap());
+
+        let (st, en) = WorkingDate::month_bounds(2024, 2);
+        assert_eq!(st, WorkingDate::parse("2024-02-01").unwrap());
+        assert_eq!(en, WorkingDate::parse("2024-02-29").unwrap());
+    }
+
+    #[test]
+    fn test_workingdate_containing_month() {
+        let date = WorkingDate::parse("2021-12-31").unwrap();
+        let (st, en) = date.containing_month();
+        assert_eq!(st, WorkingDate::parse("2021-12-01").unwrap());
+        assert_eq!(en, date);
+
+        let date = WorkingDate::parse("2022-01-01").unwrap();
+        let (st, en) = date.containing_month();
+        assert_eq!(st, date);
+        assert_eq!(en, WorkingDate::parse("2022-01-31").unwrap());
+    }
+
+    #[test]
+    fn test_workingdate_year_start() {
+        let date = WorkingDate::parse("2021-12-31").unwrap();
+        assert_eq!(date.year_start(), WorkingDate::parse("2021-01-01").unwrap());
+        let date = WorkingDate::parse("2022-01-01").unwrap();
+        assert_eq!(date.year_start(), date);
+    }
+
+    #[test]
+    fn test_workingdate_parse_isoweek() {
+        let (st, en) = WorkingDate::parse_isoweek("2024-W12").unwrap();
+        assert_eq!(st, WorkingDate::parse("2024-03-18").unwrap());
+        assert_eq!(en, WorkingDate::parse("2024-03-24").unwrap());
+
+        let (st, en) = WorkingDate::parse_isoweek("2024W12").unwrap();
+        assert_eq!(st, WorkingDate::parse("2024-03-18").unwrap());
+        assert_eq!(en, WorkingDate::parse("2024-03-24").unwrap());
+
+        // week 1 starting in the previous year
+        let (st, en) = WorkingDate::parse_isoweek("2025-W01").unwrap();
+        assert_eq!(st, WorkingDate::parse("2024-12-30").unwrap());
+        assert_eq!(en, WorkingDate::parse("2025-01-05").unwrap());
+
+        // week 53 ending in the next year
+        let (st, en) = WorkingDate::parse_isoweek("2020-W53").unwrap();
+        assert_eq!(st, WorkingDate::parse("2020-12-28").unwrap());
+        assert_eq!(en, WorkingDate::parse("2021-01-03").unwrap());
+
+        // 2021 has only 52 weeks
+        assert!(WorkingDate::parse_isoweek("2021-W53").is_err());
+    }
+
     #[test]
     fn test_workingdate_and_hm_opt() {
         let date = WorkingDate::parse("2021-01-01").unwrap();
@@ -353,6 +915,96 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_workingdate_end_of_day() {
+        let date = WorkingDate::parse("2021-12-31").unwrap();
+        let expected = TaskTime::parse("2022-01-01T05:00:00").unwrap();
+        assert_eq!(date.end_of_day(), expected);
+        assert_eq!(
+            WorkingDate::from(date.end_of_day()),
+            WorkingDate::parse("2022-01-01").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_workingdate_days_since() {
+        let date = WorkingDate::parse("2021-03-01").unwrap();
+        let earlier = WorkingDate::parse("2021-02-27").unwrap();
+        assert_eq!(date.days_since(&earlier), 2);
+        assert_eq!(earlier.days_since(&date), -2);
+        assert_eq!(date.days_since(&date), 0);
+    }
+
+    #[test]
+    fn test_workingdate_parse_window() {
+        let date = WorkingDate::parse("2021-01-01").unwrap();
+        let (begin, end) = date.parse_window("09:00-12:00").unwrap();
+        assert_eq!(begin, TaskTime::parse("2021-01-01T09:00:00").unwrap());
+        assert_eq!(end, TaskTime::parse("2021-01-01T12:00:00").unwrap());
+
+        // across midnight
+        let (begin, end) = date.parse_window("22:00 - 0130").unwrap();
+        assert_eq!(begin, TaskTime::parse("2021-01-01T22:00:00").unwrap());
+        assert_eq!(end, TaskTime::parse("2021-01-02T01:30:00").unwrap());
+
+        assert!(date.parse_window("12:00-09:00").is_err());
+        assert!(date.parse_window("09:00").is_err());
+        assert!(date.parse_window("09:00-25:00").is_err());
+    }
+
+    #[test]
+    fn test_workingdate_iter_days() {
+        let first = WorkingDate::parse("2021-12-30").unwrap();
+        let last = WorkingDate::parse("2022-01-02").unwrap();
+        let days = first
+            .iter_days(&last)
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            days,
+            vec!["2021-12-30", "2021-12-31", "2022-01-01", "2022-01-02"]
+        );
+
+        assert_eq!(first.iter_days(&first).count(), 1);
+        assert_eq!(last.iter_days(&first).count(), 0);
+    }
+
+    #[test]
+    fn test_parse_weekdays() {
+        assert_eq!(parse_weekdays("sat,sun").unwrap(), DEFAULT_WEEKEND);
+        assert_eq!(
+            parse_weekdays("Fri, saturday, fri").unwrap(),
+            vec![Weekday::Fri, Weekday::Sat]
+        );
+        assert!(parse_weekdays("").unwrap().is_empty());
+        assert!(parse_weekdays("sat,xyz").is_err());
+    }
+
+    #[test]
+    fn test_is_working_day() {
+        // 2024-03-15 is Friday
+        let date = |s: &str| WorkingDate::parse(s).unwrap();
+        assert!(is_working_day(&date("2024-03-15"), &DEFAULT_WEEKEND, &[]));
+        assert!(!is_working_day(&date("2024-03-16"), &DEFAULT_WEEKEND, &[]));
+        assert!(!is_working_day(&date("2024-03-17"), &DEFAULT_WEEKEND, &[]));
+
+        let weekend = [Weekday::Fri, Weekday::Sat];
+        assert!(!is_working_day(&date("2024-03-15"), &weekend, &[]));
+        assert!(is_working_day(&date("2024-03-17"), &weekend, &[]));
+
+        let holidays = [date("2024-03-20")];
+        assert!(!is_working_day(
+            &date("2024-03-20"),
+            &DEFAULT_WEEKEND,
+            &holidays
+        ));
+        assert!(is_working_day(
+            &date("2024-03-21"),
+            &DEFAULT_WEEKEND,
+            &holidays
+        ));
+    }
+
     #[test]
     fn test_workingdate_to_string() {
         let d = WorkingDate(NaiveDate::from_ymd_opt(2021, 1, 1).unwrap());
@@ -419,4 +1071,106 @@ mod tests {
         assert!(parse_date("20210100").is_err());
         assert!(parse_date("20211232").is_err());
     }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("8:00").unwrap(), TimeDelta::hours(8));
+        assert_eq!(parse_duration("07:30").unwrap(), TimeDelta::minutes(450));
+        assert_eq!(parse_duration("100:05").unwrap(), TimeDelta::minutes(6005));
+        assert_eq!(parse_duration("1h30m").unwrap(), TimeDelta::minutes(90));
+        assert_eq!(parse_duration("90m").unwrap(), TimeDelta::minutes(90));
+        assert_eq!(parse_duration("2h").unwrap(), TimeDelta::hours(2));
+        assert_eq!(parse_duration("0:45").unwrap(), TimeDelta::minutes(45));
+
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("8").is_err());
+        assert!(parse_duration("8:60").is_err());
+        assert!(parse_duration("1h 30m").is_err());
+        assert_eq!(
+            parse_duration("-1:00").unwrap_err().to_string(),
+            "negative duration"
+        );
+        assert_eq!(
+            parse_duration("-30m").unwrap_err().to_string(),
+            "negative duration"
+        );
+        // too large for `TimeDelta`, which would otherwise panic
+        assert_eq!(
+            parse_duration("9999999999999999h").unwrap_err().to_string(),
+            "duration out of range"
+        );
+        assert_eq!(
+            parse_duration("9999999999999999:00")
+                .unwrap_err()
+                .to_string(),
+            "duration out of range"
+        );
+    }
+
+    #[test]
+    fn test_parse_duration_units() {
+        assert_eq!(parse_duration_units("90m").unwrap(), TimeDelta::minutes(90));
+        assert_eq!(
+            parse_duration_units("1h30m").unwrap(),
+            TimeDelta::minutes(90)
+        );
+        assert_eq!(parse_duration_units("2h").unwrap(), TimeDelta::hours(2));
+
+        assert!(parse_duration_units("").is_err());
+        assert!(parse_duration_units("+30m").is_err());
+        assert!(parse_duration_units("1:30").is_err());
+        assert!(parse_duration_units("30m1h").is_err());
+    }
+
+    #[test]
+    fn test_parse_shift() {
+        assert_eq!(parse_shift("+30m").unwrap(), TimeDelta::minutes(30));
+        assert_eq!(parse_shift("-1h15m").unwrap(), TimeDelta::minutes(-75));
+        assert_eq!(parse_shift("+2h").unwrap(), TimeDelta::hours(2));
+        assert_eq!(parse_shift("+90m").unwrap(), TimeDelta::minutes(90));
+
+        assert!(parse_shift("30m").is_err());
+        assert!(parse_shift("+").is_err());
+        assert!(parse_shift("+1:30").is_err());
+        assert!(parse_shift("-15m1h").is_err());
+    }
+
+    #[test]
+    fn test_tasktime_shift() {
+        let time = TaskTime::parse("2024-03-18T23:45:00").unwrap();
+        assert_eq!(
+            time.shift(TimeDelta::minutes(30)),
+            TaskTime::parse("2024-03-19T00:15:00").unwrap()
+        );
+        assert_eq!(
+            time.shift(TimeDelta::minutes(-45)),
+            TaskTime::parse("2024-03-18T23:00:00").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_utc_offset() {
+        assert_eq!(
+            parse_utc_offset("+09:00").unwrap(),
+            FixedOffset::east_opt(9 * 3600).unwrap()
+        );
+        assert_eq!(
+            parse_utc_offset("-0530").unwrap(),
+            FixedOffset::west_opt(5 * 3600 + 30 * 60).unwrap()
+        );
+
+        assert!(parse_utc_offset("09:00").is_err());
+        assert!(parse_utc_offset("+9").is_err());
+        assert!(parse_utc_offset("+25:00").is_err());
+    }
+
+    #[test]
+    fn test_parse_isoweek() {
+        assert_eq!(parse_isoweek("2024-W01").unwrap(), (2024, 1));
+        assert_eq!(parse_isoweek("2024W53").unwrap(), (2024, 53));
+
+        assert!(parse_isoweek("2024-W00").is_err());
+        assert!(parse_isoweek("2024-W54").is_err());
+        assert!(parse_isoweek("2024-12").is_err());
+    }
 }
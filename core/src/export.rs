@@ -0,0 +1,306 @@
+use std::error::Error;
+
+use crate::datetime::{TaskTime, WorkingDate};
+use crate::task::{parse_tags, Task, TaskRecord};
+
+/// Header line of CSV export.
+pub const CSV_HEADER: &str =
+    "id,date,begin,end,level1,level2,level3,description,is_break,note,tags";
+
+/// Header line of CSV exported before tags were added.
+const CSV_HEADER_NO_TAGS: &str = "id,date,begin,end,level1,level2,level3,description,is_break,note";
+
+/// Converts a record to a CSV line (without a line break).
+pub fn to_csv_row(record: &TaskRecord) -> String {
+    let level = |i: usize| {
+        record
+            .task
+            .task
+            .get(i)
+            .cloned()
+            .flatten()
+            .unwrap_or_default()
+    };
+    [
+        record.id.map_or("".into(), |id| id.to_string()),
+        record.working_date.to_string(),
+        record.begin.to_string(),
+        record.end.as_ref().map_or("".into(), |t| t.to_string()),
+        level(0),
+        level(1),
+        level(2),
+        record.task.description.clone(),
+        (record.task.is_break as u8).to_string(),
+        record.note.clone().unwrap_or_default(),
+        record.tags.join(","),
+    ]
+    .iter()
+    .map(|field| escape_csv(field))
+    .collect::<Vec<_>>()
+    .join(",")
+}
+
+/// Parses CSV in the format of `CSV_HEADER` and `to_csv_row`.
+///
+/// Ids of records are dropped, and tasks are given without ids, as they are to be imported
+/// into another database. CSV exported before tags were added is also accepted.
+pub fn parse_csv(input: &str) -> Result<Vec<TaskRecord>, Box<dyn Error>> {
+    let (records, skipped) = parse_csv_lenient(input)?;
+    match skipped.first() {
+        Some((line, reason)) => Err(format!("line {}: {}", line, reason).into()),
+        None => Ok(records),
+    }
+}
+
+/// Line number of a CSV row that could not be parsed, and the reason.
+pub type SkippedRow = (usize, String);
+
+/// Parses CSV like `parse_csv`, but skips rows that cannot be parsed instead of failing.
+///
+/// Fails only if the header is missing.
+pub fn parse_csv_lenient(
+    input: &str,
+) -> Result<(Vec<TaskRecord>, Vec<SkippedRow>), Box<dyn Error>> {
+    let mut rows = split_csv(input)?.into_iter();
+
+    let has_tags = match rows.next().map(|(_, header)| header.join(",")) {
+        Some(header) if header == CSV_HEADER => true,
+        Some(header) if header == CSV_HEADER_NO_TAGS => false,
+        _ => return Err("missing CSV header".into()),
+    };
+
+    let mut records = vec![];
+    let mut skipped = vec![];
+    for (line, mut fields) in rows {
+        if !has_tags {
+            fields.push("".into());
+        }
+        match from_csv_fields(&fields) {
+            Ok(record) => records.push(record),
+            Err(e) => skipped.push((line, e.to_string())),
+        }
+    }
+    Ok((records, skipped))
+}
+
+/// Converts CSV fields to a record.
+fn from_csv_fields(fields: &[String]) -> Result<TaskRecord, Box<dyn Error>> {
+    let [_id, date, begin, end, level1, level2, level3, description, is_break, note, tags] = fields
+    else {
+        return Err(format!("expected 11 fields, found {}", fields.len()).into());
+    };
+
+    let non_empty = |s: &String| Some(s.clone()).filter(|s| !s.is_empty());
+    let is_break = match is_break.as_str() {
+        "0" => false,
+        "1" => true,
+        _ => return Err(format!("invalid is_break: {}", is_break).into()),
+    };
+    let task = Task::new(
+        None,
+        non_empty(level1).as_deref(),
+        non_empty(level2).as_deref(),
+        non_empty(level3).as_deref(),
+        description,
+        is_break,
+        true,
+    );
+
+    let mut record = TaskRecord::new(
+        None,
+        task,
+        WorkingDate::parse(date)?,
+        TaskTime::parse(begin)?,
+        non_empty(end)
+            .map(|end| TaskTime::parse(&end))
+            .transpose()?,
+    );
+    record.note = non_empty(note);
+    record.tags = parse_tags(tags);
+    Ok(record)
+}
+
+/// Line number and fields of a CSV row.
+type CsvRow = (usize, Vec<String>);
+
+/// Splits CSV into rows of fields, paired with the line number where each row starts.
+/// Quoted fields may contain commas, escaped double quotes and line breaks. Empty lines are skipped.
+fn split_csv(input: &str) -> Result<Vec<CsvRow>, Box<dyn Error>> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut row_line = 1;
+
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\n' => {
+                if !row.is_empty() || !field.is_empty() {
+                    row.push(std::mem::take(&mut field));
+                    rows.push((row_line, std::mem::take(&mut row)));
+                }
+                line += 1;
+                row_line = line;
+            }
+            '\r' => {}
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(format!("line {}: unterminated quoted field", row_line).into());
+    }
+    if !row.is_empty() || !field.is_empty() {
+        row.push(field);
+        rows.push((row_line, row));
+    }
+    Ok(rows)
+}
+
+/// Quotes a field if it contains a comma, a double quote or a line break.
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_csv() {
+        assert_eq!(escape_csv("abc"), "abc");
+        assert_eq!(escape_csv("a,b"), "\"a,b\"");
+        assert_eq!(escape_csv("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape_csv("a\nb"), "\"a\nb\"");
+    }
+
+    #[test]
+    fn test_split_csv() {
+        let input = "a,b\r\n\n\"c,\"\"d\"\"\",\"e\nf\"\n,\n";
+        let rows = split_csv(input).unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                (1, vec!["a".to_string(), "b".to_string()]),
+                (3, vec!["c,\"d\"".to_string(), "e\nf".to_string()]),
+                (5, vec!["".to_string(), "".to_string()]),
+            ]
+        );
+
+        assert!(split_csv("a,\"b\nc").is_err());
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let input = format!(
+            "{}\n\
+            3,2021-01-01,2021-01-01T22:00:00,2021-01-02T01:30:00,a,,,\"d, e\",0,\"x\ny\",\"T-1,T-2\"\n\
+            4,2021-01-02,2021-01-02T09:00:00,,lunch,,,,1,,\n",
+            CSV_HEADER
+        );
+        let records = parse_csv(&input).unwrap();
+
+        let task = Task::new(None, Some("a"), None, None, "d, e", false, true);
+        let mut record = TaskRecord::new(
+            None,
+            task,
+            WorkingDate::parse("2021-01-01").unwrap(),
+            TaskTime::parse("2021-01-01T22:00:00").unwrap(),
+            Some(TaskTime::parse("2021-01-02T01:30:00").unwrap()),
+        );
+        record.note = Some("x\ny".into());
+        record.tags = vec!["T-1".into(), "T-2".into()];
+        assert_eq!(records[0], record);
+
+        let task = Task::new(None, Some("lunch"), None, None, "", true, true);
+        let record = TaskRecord::new(
+            None,
+            task,
+            WorkingDate::parse("2021-01-02").unwrap(),
+            TaskTime::parse("2021-01-02T09:00:00").unwrap(),
+            None,
+        );
+        assert_eq!(records[1], record);
+
+        // exported before tags were added
+        let input = format!(
+            "{}\n4,2021-01-02,2021-01-02T09:00:00,,lunch,,,,1,\n",
+            CSV_HEADER_NO_TAGS
+        );
+        assert_eq!(parse_csv(&input).unwrap(), vec![record]);
+
+        assert!(parse_csv("id,date\n").is_err());
+        let input = format!("{}\n1,2021-01-01,bad,,a,,,,0,,\n", CSV_HEADER);
+        let err = parse_csv(&input).unwrap_err();
+        assert!(err.to_string().starts_with("line 2:"));
+    }
+
+    #[test]
+    fn test_parse_csv_lenient() {
+        let input = format!(
+            "{}\n\
+            1,2021-01-01,bad,,a,,,,0,,\n\
+            2,2021-01-01,2021-01-01T09:00:00,,a,,,,0,,\n\
+            3,2021-01-01\n",
+            CSV_HEADER
+        );
+        let (records, skipped) = parse_csv_lenient(&input).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            skipped.iter().map(|(line, _)| *line).collect::<Vec<_>>(),
+            vec![2, 4]
+        );
+        assert_eq!(skipped[1].1, "expected 11 fields, found 2");
+
+        assert!(parse_csv_lenient("id,date\n").is_err());
+    }
+
+    #[test]
+    fn test_to_csv_row() {
+        let task = Task::new(Some(1), Some("a"), Some("b"), None, "d, e", false, true);
+        let mut record = TaskRecord::new(
+            Some(3),
+            task,
+            WorkingDate::parse("2021-01-01").unwrap(),
+            TaskTime::parse("2021-01-01T09:00:00").unwrap(),
+            Some(TaskTime::parse("2021-01-01T12:00:00").unwrap()),
+        );
+        assert_eq!(
+            to_csv_row(&record),
+            "3,2021-01-01,2021-01-01T09:00:00,2021-01-01T12:00:00,a,b,,\"d, e\",0,,"
+        );
+
+        record.end = None;
+        record.note = Some("memo".into());
+        record.tags = vec!["T-1".into(), "T-2".into()];
+        assert_eq!(
+            to_csv_row(&record),
+            "3,2021-01-01,2021-01-01T09:00:00,,a,b,,\"d, e\",0,memo,\"T-1,T-2\""
+        );
+    }
+}
@@ -1,4 +1,8 @@
 pub mod datetime;
+pub mod export;
 pub mod repository;
 pub mod sqlite_db;
 pub mod task;
+#[cfg(test)]
+mod test_util;
+pub mod verify;
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use crate::datetime::WorkingDate;
 use crate::task::{Task, TaskRecord};
 
@@ -19,19 +21,41 @@ pub trait Manipulation {
     fn register_task(&self, task: &Task) -> Result<()>;
     /// Unregisters (deactivate) a task specified by id.
     fn unregister_task(&self, id: u32) -> Result<()>;
+    /// Hides records of a task from record lists, keeping them in the database.
+    /// Hidden records are left out of record lookups and counts as well.
+    /// Returns the number of hidden records.
+    fn hide_records_for_task(&self, task_id: u32) -> Result<usize>;
     /// Gets all tasks.
     fn tasks(&self) -> Result<Vec<Task>>;
     /// Gets a task specified by id.
     fn get_task(&self, id: u32) -> Result<Task>;
+    /// Counts all tasks.
+    fn task_count(&self) -> Result<u64>;
+    /// Counts records by task id. Tasks without records are not included.
+    fn record_counts_by_task(&self) -> Result<HashMap<u32, u64>>;
 
     /// Gets the state of the current record.
     fn current_state(&self, date: &WorkingDate) -> Result<State>;
+    /// Gets the latest record in a specified date, whether it is ended or not.
+    fn latest_record(&self, date: &WorkingDate) -> Result<Option<TaskRecord>>;
+    /// Gets the latest record that is not ended, regardless of the date.
+    fn latest_open_record(&self) -> Result<Option<TaskRecord>>;
+    /// Gets the latest record belonging to a paused session, regardless of the date.
+    fn latest_session_record(&self) -> Result<Option<TaskRecord>>;
     /// Creates/updates a record.
     fn add_record(&self, record: &TaskRecord) -> Result<()>;
     /// Deletes a record.
     fn delete_record(&self, id: u32) -> Result<()>;
+    /// Gets a record specified by id.
+    fn get_record(&self, id: u32) -> Result<TaskRecord>;
     /// Gets all records.
     fn records(&self) -> Result<Vec<TaskRecord>>;
+    /// Counts all records.
+    fn record_count(&self) -> Result<u64>;
+    /// Gets the working date of the earliest record.
+    fn earliest_date(&self) -> Result<Option<WorkingDate>>;
+    /// Gets all records of a task specified by id.
+    fn records_for_task(&self, task_id: u32) -> Result<Vec<TaskRecord>>;
     /// Gets records in a specified date.
     fn get_records_by_date(&self, date: &WorkingDate) -> Result<Vec<TaskRecord>>;
     /// Gets records in between the dates.
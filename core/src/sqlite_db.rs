@@ -1,15 +1,44 @@
+use std::collections::HashMap;
 use std::path::Path;
+use std::thread;
+use std::time::Duration;
 
 use chrono::{NaiveDate, NaiveDateTime};
 use rusqlite::config::DbConfig::SQLITE_DBCONFIG_ENABLE_FKEY;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, DatabaseName, ErrorCode, Params, Row};
 
-use crate::datetime::WorkingDate;
+use crate::datetime::{now_exact, TaskTime, WorkingDate};
 use crate::repository::{Manipulation, Result, State};
-use crate::task::{Task, TaskRecord};
+use crate::task::{parse_tags, Task, TaskRecord};
 
 pub use rusqlite::OpenFlags;
 
+/// Schema migrations. The `i`-th one brings a database from version `i` to `i + 1`.
+///
+/// The schema version is kept in `PRAGMA user_version`, and version 0 is the schema before
+/// migrations were introduced. Databases created between schema changes may already have
+/// some of the columns without a version, so the columns are added only if missing.
+const MIGRATIONS: [fn(&SQLiteDatabase) -> Result<()>; 6] = [
+    |db| db.add_column("records", "note", "TEXT"),
+    |db| {
+        db.add_column("records", "begin_exact", "TEXT")?;
+        db.add_column("records", "end_exact", "TEXT")
+    },
+    |db| db.add_column("records", "tags", "TEXT"),
+    |db| db.add_column("records", "session_id", "INTEGER"),
+    |db| db.add_column("tasks", "is_billable", "INTEGER DEFAULT 1"),
+    |db| db.add_column("records", "is_hidden", "INTEGER DEFAULT 0"),
+];
+
+/// Attempts of a write while the database is locked by another connection.
+pub const BUSY_RETRY_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry of a write, doubled on each retry.
+pub const BUSY_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Schema version of databases created or migrated by this version.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
 /// Database connection.
 pub struct SQLiteDatabase {
     conn: Connection,
@@ -45,9 +74,13 @@ impl SQLiteDatabase {
     /// Creates tables if they do not exist.
     pub fn initialize(&self) -> Result<()> {
         self.setup()?;
+        self.transaction(|db| db.create_tables())
+    }
+
+    /// Recreates tables of the current schema.
+    fn create_tables(&self) -> Result<()> {
         self.conn.execute_batch(
-            "BEGIN;\
-            DROP TABLE IF EXISTS tasks;\
+            "DROP TABLE IF EXISTS tasks;\
             DROP TABLE IF EXISTS records;\
             CREATE TABLE tasks (\
                 id INTEGER PRIMARY KEY AUTOINCREMENT,\
@@ -56,7 +89,8 @@ impl SQLiteDatabase {
                 level3 TEXT,\
                 description TEXT,\
                 is_break INTEGER,\
-                is_active INTEGER\
+                is_active INTEGER,\
+                is_billable INTEGER DEFAULT 1\
             );\
             CREATE TABLE records (\
                 id INTEGER PRIMARY KEY AUTOINCREMENT,\
@@ -65,20 +99,272 @@ impl SQLiteDatabase {
                 begin TEXT,\
                 end TEXT,\
                 is_break INTEGER,\
+                note TEXT,\
+                begin_exact TEXT,\
+                end_exact TEXT,\
+                tags TEXT,\
+                session_id INTEGER,\
+                is_hidden INTEGER DEFAULT 0,\
                 FOREIGN KEY(task_id) REFERENCES tasks(id)\
-            );\
-            COMMIT;",
+            );",
+        )?;
+        self.conn
+            .pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(())
+    }
+
+    /// Schema version of the database.
+    pub fn schema_version(&self) -> Result<u32> {
+        let version = self
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+        Ok(version)
+    }
+
+    /// Checks the database file for corruption. Returns the problems found, if any.
+    pub fn integrity_check(&self) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare("PRAGMA integrity_check")?;
+        let messages = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(messages.into_iter().filter(|m| m != "ok").collect())
+    }
+
+    /// Applies migrations up to `SCHEMA_VERSION` in a transaction.
+    ///
+    /// Databases without tables are left as they are, to be initialized.
+    fn migrate(&self) -> Result<()> {
+        let version = self.schema_version()?;
+        if version > SCHEMA_VERSION {
+            return Err(format!(
+                "database schema version {} is newer than supported version {}",
+                version, SCHEMA_VERSION
+            )
+            .into());
+        }
+        if version == SCHEMA_VERSION || !self.is_ready()? {
+            return Ok(());
+        }
+
+        self.transaction(|db| {
+            for migration in &MIGRATIONS[version as usize..] {
+                migration(db)?;
+            }
+            db.conn
+                .pragma_update(None, "user_version", SCHEMA_VERSION)?;
+            Ok(())
+        })
+    }
+
+    /// Adds a column to a table unless it exists.
+    fn add_column(&self, table: &str, column: &str, decl: &str) -> Result<()> {
+        let count = self.conn.query_row(
+            "SELECT count(*) FROM pragma_table_info(?1) WHERE name = ?2",
+            params![table, column],
+            |row| row.get::<_, u32>(0),
         )?;
+        if count == 0 {
+            self.conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl),
+                [],
+            )?;
+        }
         Ok(())
     }
 
+    /// Runs `f` in a transaction.
+    ///
+    /// Changes made in `f` are committed if it succeeds, and rolled back if it returns an error.
+    /// The write lock is taken at the beginning, since a busy error on upgrading the lock in
+    /// the middle could not be resolved by retrying a statement. Beginning and committing
+    /// are retried while the database is busy or locked.
+    pub fn transaction<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Self) -> Result<T>,
+    {
+        retry_busy(|| self.conn.execute_batch("BEGIN IMMEDIATE"))?;
+        let result = f(self).and_then(|result| {
+            retry_busy(|| self.conn.execute_batch("COMMIT"))?;
+            Ok(result)
+        });
+        if result.is_err() && !self.conn.is_autocommit() {
+            self.conn.execute_batch("ROLLBACK")?;
+        }
+        result
+    }
+
+    /// Adds records as new ones in a single transaction.
+    ///
+    /// Tasks are matched by their names and description, ignoring ids, as `register_task`
+    /// tells them apart. Tasks that are not found are registered. Returns the number of added records.
+    pub fn import_records(&self, records: &[TaskRecord]) -> Result<usize> {
+        self.transaction(|db| {
+            let mut tasks = db.tasks()?;
+            for record in records {
+                let mut record = record.clone();
+                record.id = None;
+                record.task = match tasks.iter().find(|task| same_task(task, &record.task)) {
+                    Some(task) => task.clone(),
+                    None => {
+                        let mut task = record.task.clone();
+                        task.id = None;
+                        db.register_task(&task)?;
+                        task.id = Some(db.conn.last_insert_rowid() as u32);
+                        tasks.push(task.clone());
+                        task
+                    }
+                };
+                db.add_record(&record)?;
+            }
+            Ok(records.len())
+        })
+    }
+
+    /// Truncates seconds of stored begin/end times so that they agree with `TaskTime`.
+    ///
+    /// Records are re-stored through `add_record` in a single transaction.
+    /// Returns the number of changed records.
+    pub fn normalize_records(&self) -> Result<usize> {
+        self.transaction(|db| db.normalize_records_inner())
+    }
+
+    fn normalize_records_inner(&self) -> Result<usize> {
+        let stored = self
+            .conn
+            .prepare("SELECT id, begin, end FROM records")?
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, u32>(0)?,
+                    (
+                        row.get::<_, NaiveDateTime>(1)?,
+                        row.get::<_, Option<NaiveDateTime>>(2)?,
+                    ),
+                ))
+            })?
+            .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+
+        // hidden records are normalized as well
+        let mut stmt = self.conn.prepare(
+            "SELECT \
+                r.id, r.working_date, r.begin, r.end, r.note,\
+                t.id, t.level1, t.level2, t.level3, t.description, t.is_break, t.is_active, r.tags, r.session_id, t.is_billable \
+            FROM records AS r \
+            LEFT JOIN tasks AS t \
+            ON r.task_id = t.id",
+        )?;
+        let records = stmt
+            .query_map([], record_from_row)?
+            .flatten()
+            .collect::<Vec<_>>();
+
+        let mut count = 0;
+        for record in records {
+            let begin = NaiveDateTime::from(record.begin.clone());
+            let end = record.end.clone().map(NaiveDateTime::from);
+            let changed = record
+                .id
+                .and_then(|id| stored.get(&id))
+                .is_some_and(|(b, e)| b != &begin || e != &end);
+            if changed {
+                self.add_record(&record)?;
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Executes a write statement, retrying while the database is busy or locked.
+    ///
+    /// The busy timeout of the connection may run out under heavy concurrent use,
+    /// so the statement is retried up to `BUSY_RETRY_ATTEMPTS` times with backoff.
+    fn execute<P: Params + Copy>(&self, sql: &str, params: P) -> rusqlite::Result<usize> {
+        retry_busy(|| self.conn.execute(sql, params))
+    }
+
     /// Applies configulations to the database.
     fn setup(&self) -> Result<()> {
         let _ = self.conn.set_db_config(SQLITE_DBCONFIG_ENABLE_FKEY, true)?;
+        // read-only connections cannot migrate, and rely on a prior writable one
+        if !self.conn.is_readonly(DatabaseName::Main)? {
+            self.migrate()?;
+        }
         Ok(())
     }
 }
 
+/// Runs a statement by `f`, retrying while the database is busy or locked.
+fn retry_busy<T>(f: impl Fn() -> rusqlite::Result<T>) -> rusqlite::Result<T> {
+    let mut delay = BUSY_RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        match f() {
+            Err(e) if is_busy(&e) && attempt < BUSY_RETRY_ATTEMPTS => {
+                thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether the error is due to another connection holding a lock.
+fn is_busy(error: &rusqlite::Error) -> bool {
+    matches!(
+        error.sqlite_error_code(),
+        Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+    )
+}
+
+/// Whether an error is returned from the database.
+pub fn is_database_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    error.is::<rusqlite::Error>()
+}
+
+/// Whether an error returned from the database is due to another connection holding a lock.
+pub fn is_locked_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    error.downcast_ref::<rusqlite::Error>().is_some_and(is_busy)
+}
+
+/// Builds a `TaskRecord` from a row of records joined with tasks.
+///
+/// The columns are expected in the order of
+/// `r.id, r.working_date, r.begin, r.end, r.note,
+/// t.id, t.level1, t.level2, t.level3, t.description, t.is_break, t.is_active,
+/// r.tags, r.session_id, t.is_billable`.
+fn record_from_row(row: &Row) -> rusqlite::Result<TaskRecord> {
+    let mut task = Task::new(
+        row.get::<_, u32>(5).ok(),
+        row.get::<_, String>(6).ok().as_deref(),
+        row.get::<_, String>(7).ok().as_deref(),
+        row.get::<_, String>(8).ok().as_deref(),
+        &row.get::<_, String>(9).unwrap_or_default(),
+        row.get::<_, u8>(10).unwrap_or_default() != 0,
+        row.get::<_, u8>(11).unwrap_or_default() != 0,
+    );
+    task.is_billable = row.get::<_, Option<u8>>(14)?.unwrap_or(1) != 0;
+    let end_raw = row.get::<_, Option<NaiveDateTime>>(3)?;
+    let mut record = TaskRecord::new(
+        row.get::<_, u32>(0).ok(),
+        task,
+        row.get::<_, NaiveDate>(1)?.into(),
+        row.get::<_, NaiveDateTime>(2)?.into(),
+        end_raw.map(|t| t.into()),
+    );
+    record.note = row.get::<_, Option<String>>(4)?;
+    record.tags = row
+        .get::<_, Option<String>>(12)?
+        .map_or_else(Vec::new, |tags| parse_tags(&tags));
+    record.session_id = row.get::<_, Option<u32>>(13)?;
+    Ok(record)
+}
+
+/// Whether two tasks have the same names and description, which identify a task.
+fn same_task(a: &Task, b: &Task) -> bool {
+    a.task == b.task && a.description == b.description
+}
+
 impl Manipulation for SQLiteDatabase {
     fn is_ready(&self) -> Result<bool> {
         let table_count = self.conn.query_row(
@@ -93,10 +379,37 @@ impl Manipulation for SQLiteDatabase {
     }
 
     fn register_task(&self, task: &Task) -> Result<()> {
+        // active tasks of the same name and description cannot be told apart
+        if task.is_active {
+            let duplicates = retry_busy(|| {
+                self.conn.query_row(
+                    "SELECT count(*) FROM tasks \
+                    WHERE level1 IS ?1 AND level2 IS ?2 AND level3 IS ?3 AND description = ?4 \
+                    AND is_active = 1 AND id IS NOT ?5",
+                    params![
+                        task.task[0],
+                        task.task[1],
+                        task.task[2],
+                        task.description,
+                        task.id,
+                    ],
+                    |row| row.get::<_, u32>(0),
+                )
+            })?;
+            if duplicates > 0 {
+                return Err(format!(
+                    "task {} is already registered",
+                    task.format_name_or("/", "[no name]")
+                )
+                .into());
+            }
+        }
+
         if let Some(id) = task.id {
-            self.conn.execute(
+            self.execute(
                 "UPDATE tasks \
-                SET level1 = ?1, level2 = ?2, level3 = ?3, description = ?4, is_break = ?5, is_active = ?6 \
+                SET level1 = ?1, level2 = ?2, level3 = ?3, description = ?4, is_break = ?5, is_active = ?6, \
+                is_billable = ?8 \
                 WHERE id = ?7",
                 params![
                     task.task[0],
@@ -106,12 +419,14 @@ impl Manipulation for SQLiteDatabase {
                     task.is_break as u8,
                     task.is_active as u8,
                     id,
+                    task.is_billable as u8,
                 ],
             )?
         } else {
-            self.conn.execute(
-                "INSERT INTO tasks (level1, level2, level3, description, is_break, is_active) \
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            self.execute(
+                "INSERT INTO tasks \
+                (level1, level2, level3, description, is_break, is_active, is_billable) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
                 params![
                     task.task[0],
                     task.task[1],
@@ -119,6 +434,7 @@ impl Manipulation for SQLiteDatabase {
                     task.description,
                     task.is_break as u8,
                     task.is_active as u8,
+                    task.is_billable as u8,
                 ],
             )?
         };
@@ -126,7 +442,7 @@ impl Manipulation for SQLiteDatabase {
     }
 
     fn unregister_task(&self, id: u32) -> Result<()> {
-        self.conn.execute(
+        self.execute(
             "UPDATE tasks \
              SET is_active = 0 WHERE id = ?1",
             params![id],
@@ -134,14 +450,22 @@ impl Manipulation for SQLiteDatabase {
         Ok(())
     }
 
+    fn hide_records_for_task(&self, task_id: u32) -> Result<usize> {
+        let count = self.execute(
+            "UPDATE records SET is_hidden = 1 WHERE task_id = ?1",
+            params![task_id],
+        )?;
+        Ok(count)
+    }
+
     fn tasks(&self) -> Result<Vec<Task>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, level1, level2, level3, description, is_break, is_active FROM tasks \
-            ORDER BY level1, level2, level3",
+            "SELECT id, level1, level2, level3, description, is_break, is_active, is_billable \
+            FROM tasks ORDER BY level1, level2, level3, id",
         )?;
 
         let rows = stmt.query_map([], |row| {
-            let task = Task::new(
+            let mut task = Task::new(
                 row.get::<_, u32>(0).ok(),
                 row.get::<_, String>(1).ok().as_deref(),
                 row.get::<_, String>(2).ok().as_deref(),
@@ -150,6 +474,7 @@ impl Manipulation for SQLiteDatabase {
                 row.get::<_, u8>(5).unwrap() != 0,
                 row.get::<_, u8>(6).unwrap() != 0,
             );
+            task.is_billable = row.get::<_, Option<u8>>(7)?.unwrap_or(1) != 0;
             Ok(task)
         })?;
 
@@ -159,11 +484,11 @@ impl Manipulation for SQLiteDatabase {
 
     fn get_task(&self, id: u32) -> Result<Task> {
         let task = self.conn.query_row(
-            "SELECT level1, level2, level3, description, is_break, is_active FROM tasks \
-            WHERE id = ?1",
+            "SELECT level1, level2, level3, description, is_break, is_active, is_billable \
+            FROM tasks WHERE id = ?1",
             params![id],
             |row| {
-                let task = Task::new(
+                let mut task = Task::new(
                     Some(id),
                     row.get::<_, String>(0).ok().as_deref(),
                     row.get::<_, String>(1).ok().as_deref(),
@@ -172,6 +497,7 @@ impl Manipulation for SQLiteDatabase {
                     row.get::<_, u8>(4).unwrap() != 0,
                     row.get::<_, u8>(5).unwrap() != 0,
                 );
+                task.is_billable = row.get::<_, Option<u8>>(6)?.unwrap_or(1) != 0;
                 Ok(task)
             },
         )?;
@@ -179,74 +505,126 @@ impl Manipulation for SQLiteDatabase {
         Ok(task)
     }
 
+    fn task_count(&self) -> Result<u64> {
+        let count = self
+            .conn
+            .query_row("SELECT count(*) FROM tasks", [], |row| row.get(0))?;
+        Ok(count)
+    }
+
+    fn record_counts_by_task(&self) -> Result<HashMap<u32, u64>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT task_id, count(*) FROM records \
+            WHERE task_id IS NOT NULL AND is_hidden IS NOT 1 \
+            GROUP BY task_id",
+        )?;
+
+        let counts = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(counts)
+    }
+
     fn current_state(&self, date: &WorkingDate) -> Result<State> {
+        match self.latest_record(date)? {
+            Some(task_record) if task_record.end.is_none() => Ok(State::Active(task_record)),
+            _ => Ok(State::Completed),
+        }
+    }
+
+    fn latest_record(&self, date: &WorkingDate) -> Result<Option<TaskRecord>> {
         let mut stmt = self.conn.prepare(
             "SELECT \
-                r.id, r.working_date, r.begin, r.end,\
-                t.id, t.level1, t.level2, t.level3, t.description, t.is_break, t.is_active \
-            FROM (SELECT * FROM records WHERE working_date = ?1 ORDER BY working_date DESC, begin DESC LIMIT 1) AS r \
+                r.id, r.working_date, r.begin, r.end, r.note,\
+                t.id, t.level1, t.level2, t.level3, t.description, t.is_break, t.is_active, r.tags, r.session_id, t.is_billable \
+            FROM (SELECT * FROM records WHERE working_date = ?1 AND is_hidden IS NOT 1 ORDER BY working_date DESC, begin DESC, id DESC LIMIT 1) AS r \
             LEFT JOIN tasks AS t \
             ON r.task_id = t.id",
         )?;
 
-        let task_record = stmt.query_map(params![NaiveDate::from(date)], |row| {
-            let task = Task::new(
-                row.get::<_, u32>(4).ok(),
-                row.get::<_, String>(5).ok().as_deref(),
-                row.get::<_, String>(6).ok().as_deref(),
-                row.get::<_, String>(7).ok().as_deref(),
-                &row.get::<_, String>(8).unwrap_or_default(),
-                row.get::<_, u8>(9).unwrap() != 0,
-                row.get::<_, u8>(10).unwrap() != 0,
-            );
-            let end_raw = row.get::<_, Option<NaiveDateTime>>(3).unwrap();
-            let record = TaskRecord::new(
-                row.get::<_, u32>(0).ok(),
-                task,
-                row.get::<_, NaiveDate>(1).unwrap().into(),
-                row.get::<_, NaiveDateTime>(2).unwrap().into(),
-                end_raw.map(|t| t.into()),
-            );
-            Ok(record)
-        })?;
+        let mut records = stmt
+            .query_map(params![NaiveDate::from(date)], record_from_row)?
+            .flatten();
+        Ok(records.next())
+    }
 
-        let task_records = task_record.flatten().collect::<Vec<_>>();
+    fn latest_open_record(&self) -> Result<Option<TaskRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT \
+                r.id, r.working_date, r.begin, r.end, r.note,\
+                t.id, t.level1, t.level2, t.level3, t.description, t.is_break, t.is_active, r.tags, r.session_id, t.is_billable \
+            FROM (SELECT * FROM records WHERE end IS NULL AND is_hidden IS NOT 1 ORDER BY working_date DESC, begin DESC, id DESC LIMIT 1) AS r \
+            LEFT JOIN tasks AS t \
+            ON r.task_id = t.id",
+        )?;
 
-        if task_records.is_empty() {
-            return Ok(State::Completed);
-        }
+        let mut records = stmt.query_map([], record_from_row)?.flatten();
+        Ok(records.next())
+    }
 
-        let task_record = task_records[0].clone();
+    fn latest_session_record(&self) -> Result<Option<TaskRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT \
+                r.id, r.working_date, r.begin, r.end, r.note,\
+                t.id, t.level1, t.level2, t.level3, t.description, t.is_break, t.is_active, r.tags, r.session_id, t.is_billable \
+            FROM (SELECT * FROM records WHERE session_id IS NOT NULL AND is_hidden IS NOT 1 ORDER BY working_date DESC, begin DESC, id DESC LIMIT 1) AS r \
+            LEFT JOIN tasks AS t \
+            ON r.task_id = t.id",
+        )?;
 
-        match task_record.end {
-            Some(_) => Ok(State::Completed),
-            None => Ok(State::Active(task_record)),
-        }
+        let mut records = stmt.query_map([], record_from_row)?.flatten();
+        Ok(records.next())
     }
 
     fn add_record(&self, record: &TaskRecord) -> Result<()> {
+        if let Some(end) = record.end.as_ref().filter(|end| end < &&record.begin) {
+            return Err(format!("end time {} precedes begin time {}", end, record.begin).into());
+        }
+
+        // keep the exact time for audit when a time is set to now
+        let now = now_exact();
+        let exact = |time: &TaskTime| (time == &TaskTime::from(now)).then_some(now);
+        let begin_exact = exact(&record.begin);
+        let end_exact = record.end.as_ref().and_then(exact);
+        // tags are stored comma-separated
+        let tags = Some(record.tags.join(",")).filter(|tags| !tags.is_empty());
+
         if let Some(id) = record.id {
-            self.conn.execute(
+            // exact times are kept while the times are unchanged
+            self.execute(
                 "UPDATE records \
-                SET task_id = ?1, working_date = ?2, begin = ?3, end = ?4 \
-                WHERE id = ?5",
+                SET task_id = ?1, working_date = ?2, begin = ?3, end = ?4, note = ?5, tags = ?9, session_id = ?10, \
+                    begin_exact = CASE WHEN begin IS ?3 THEN coalesce(?7, begin_exact) ELSE ?7 END, \
+                    end_exact = CASE WHEN end IS ?4 THEN coalesce(?8, end_exact) ELSE ?8 END \
+                WHERE id = ?6",
                 params![
                     record.task.id,
                     NaiveDate::from(&record.working_date),
                     NaiveDateTime::from(record.begin.clone()),
                     record.end.clone().map(NaiveDateTime::from),
+                    record.note,
                     id,
+                    begin_exact,
+                    end_exact,
+                    tags,
+                    record.session_id,
                 ],
             )?;
         } else {
-            self.conn.execute(
-                "INSERT INTO records (task_id, working_date, begin, end) \
-                VALUES (?1, ?2, ?3, ?4)",
+            self.execute(
+                "INSERT INTO records \
+                    (task_id, working_date, begin, end, note, begin_exact, end_exact, tags, session_id) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
                 params![
                     record.task.id,
                     NaiveDate::from(&record.working_date),
                     NaiveDateTime::from(record.begin.clone()),
                     record.end.clone().map(NaiveDateTime::from),
+                    record.note,
+                    begin_exact,
+                    end_exact,
+                    tags,
+                    record.session_id,
                 ],
             )?;
         }
@@ -254,42 +632,75 @@ impl Manipulation for SQLiteDatabase {
     }
 
     fn delete_record(&self, id: u32) -> Result<()> {
-        self.conn
-            .execute("DELETE FROM records WHERE id = ?1", params![id])?;
+        self.execute("DELETE FROM records WHERE id = ?1", params![id])?;
         Ok(())
     }
 
+    fn get_record(&self, id: u32) -> Result<TaskRecord> {
+        let record = self.conn.query_row(
+            "SELECT \
+                r.id, r.working_date, r.begin, r.end, r.note,\
+                t.id, t.level1, t.level2, t.level3, t.description, t.is_break, t.is_active, r.tags, r.session_id, t.is_billable \
+            FROM records AS r \
+            LEFT JOIN tasks AS t \
+            ON r.task_id = t.id \
+            WHERE r.id = ?1 AND r.is_hidden IS NOT 1",
+            params![id],
+            record_from_row,
+        )?;
+
+        Ok(record)
+    }
+
+    fn record_count(&self) -> Result<u64> {
+        let count = self.conn.query_row(
+            "SELECT count(*) FROM records WHERE is_hidden IS NOT 1",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    fn earliest_date(&self) -> Result<Option<WorkingDate>> {
+        let date = self.conn.query_row(
+            "SELECT min(working_date) FROM records WHERE is_hidden IS NOT 1",
+            [],
+            |row| row.get::<_, Option<NaiveDate>>(0),
+        )?;
+        Ok(date.map(WorkingDate::from))
+    }
+
     fn records(&self) -> Result<Vec<TaskRecord>> {
         let mut stmt = self.conn.prepare(
             "SELECT \
-                r.id, r.working_date, r.begin, r.end,\
-                t.id, t.level1, t.level2, t.level3, t.description, t.is_break, t.is_active \
+                r.id, r.working_date, r.begin, r.end, r.note,\
+                t.id, t.level1, t.level2, t.level3, t.description, t.is_break, t.is_active, r.tags, r.session_id, t.is_billable \
             FROM records AS r \
             LEFT JOIN tasks AS t \
             ON r.task_id = t.id \
-            ORDER BY working_date, begin",
+            WHERE r.is_hidden IS NOT 1 \
+            ORDER BY working_date, begin, r.id",
         )?;
 
-        let rows = stmt.query_map([], |row| {
-            let task = Task::new(
-                row.get::<_, u32>(4).ok(),
-                row.get::<_, String>(5).ok().as_deref(),
-                row.get::<_, String>(6).ok().as_deref(),
-                row.get::<_, String>(7).ok().as_deref(),
-                &row.get::<_, String>(8).unwrap_or_default(),
-                row.get::<_, u8>(9).unwrap() != 0,
-                row.get::<_, u8>(10).unwrap() != 0,
-            );
-            let end_raw = row.get::<_, Option<NaiveDateTime>>(3).unwrap();
-            let record = TaskRecord::new(
-                row.get::<_, u32>(0).ok(),
-                task,
-                row.get::<_, NaiveDate>(1).unwrap().into(),
-                row.get::<_, NaiveDateTime>(2).unwrap().into(),
-                end_raw.map(|t| t.into()),
-            );
-            Ok(record)
-        })?;
+        let rows = stmt.query_map([], record_from_row)?;
+
+        let records = rows.flatten().collect();
+        Ok(records)
+    }
+
+    fn records_for_task(&self, task_id: u32) -> Result<Vec<TaskRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT \
+                r.id, r.working_date, r.begin, r.end, r.note,\
+                t.id, t.level1, t.level2, t.level3, t.description, t.is_break, t.is_active, r.tags, r.session_id, t.is_billable \
+            FROM records AS r \
+            LEFT JOIN tasks AS t \
+            ON r.task_id = t.id \
+            WHERE r.task_id = ?1 AND r.is_hidden IS NOT 1 \
+            ORDER BY working_date, begin, r.id",
+        )?;
+
+        let rows = stmt.query_map(params![task_id], record_from_row)?;
 
         let records = rows.flatten().collect();
         Ok(records)
@@ -298,34 +709,15 @@ impl Manipulation for SQLiteDatabase {
     fn get_records_by_date(&self, date: &WorkingDate) -> Result<Vec<TaskRecord>> {
         let mut stmt = self.conn.prepare(
             "SELECT \
-                r.id, r.working_date, r.begin, r.end,\
-                t.id, t.level1, t.level2, t.level3, t.description, t.is_break, t.is_active \
-            FROM (SELECT * FROM records WHERE working_date = ?1) AS r \
+                r.id, r.working_date, r.begin, r.end, r.note,\
+                t.id, t.level1, t.level2, t.level3, t.description, t.is_break, t.is_active, r.tags, r.session_id, t.is_billable \
+            FROM (SELECT * FROM records WHERE working_date = ?1 AND is_hidden IS NOT 1) AS r \
             LEFT JOIN tasks AS t \
             ON r.task_id = t.id \
-            ORDER BY working_date, begin",
-        )?;
-
-        let rows = stmt.query_map(params![NaiveDate::from(date)], |row| {
-            let task = Task::new(
-                row.get::<_, u32>(4).ok(),
-                row.get::<_, String>(5).ok().as_deref(),
-                row.get::<_, String>(6).ok().as_deref(),
-                row.get::<_, String>(7).ok().as_deref(),
-                &row.get::<_, String>(8).unwrap_or_default(),
-                row.get::<_, u8>(9).unwrap() != 0,
-                row.get::<_, u8>(10).unwrap() != 0,
-            );
-            let end_raw = row.get::<_, Option<NaiveDateTime>>(3).unwrap();
-            let record = TaskRecord::new(
-                row.get::<_, u32>(0).ok(),
-                task,
-                row.get::<_, NaiveDate>(1).unwrap().into(),
-                row.get::<_, NaiveDateTime>(2).unwrap().into(),
-                end_raw.map(|t| t.into()),
-            );
-            Ok(record)
-        })?;
+            ORDER BY working_date, begin, r.id",
+        )?;
+
+        let rows = stmt.query_map(params![NaiveDate::from(date)], record_from_row)?;
 
         let records = rows.flatten().collect();
         Ok(records)
@@ -338,34 +730,18 @@ impl Manipulation for SQLiteDatabase {
     ) -> Result<Vec<TaskRecord>> {
         let mut stmt = self.conn.prepare(
             "SELECT \
-                r.id, r.working_date, r.begin, r.end,\
-                t.id, t.level1, t.level2, t.level3, t.description, t.is_break, t.is_active \
-            FROM (SELECT * FROM records WHERE working_date BETWEEN ?1 AND ?2) AS r \
+                r.id, r.working_date, r.begin, r.end, r.note,\
+                t.id, t.level1, t.level2, t.level3, t.description, t.is_break, t.is_active, r.tags, r.session_id, t.is_billable \
+            FROM (SELECT * FROM records WHERE working_date BETWEEN ?1 AND ?2 AND is_hidden IS NOT 1) AS r \
             LEFT JOIN tasks AS t \
             ON r.task_id = t.id \
-            ORDER BY working_date, begin",
-        )?;
-
-        let rows = stmt.query_map(params![NaiveDate::from(from), NaiveDate::from(to)], |row| {
-            let task = Task::new(
-                row.get::<_, u32>(4).ok(),
-                row.get::<_, String>(5).ok().as_deref(),
-                row.get::<_, String>(6).ok().as_deref(),
-                row.get::<_, String>(7).ok().as_deref(),
-                &row.get::<_, String>(8).unwrap_or_default(),
-                row.get::<_, u8>(9).unwrap() != 0,
-                row.get::<_, u8>(10).unwrap() != 0,
-            );
-            let end_raw = row.get::<_, Option<NaiveDateTime>>(3).unwrap();
-            let record = TaskRecord::new(
-                row.get::<_, u32>(0).ok(),
-                task,
-                row.get::<_, NaiveDate>(1).unwrap().into(),
-                row.get::<_, NaiveDateTime>(2).unwrap().into(),
-                end_raw.map(|t| t.into()),
-            );
-            Ok(record)
-        })?;
+            ORDER BY working_date, begin, r.id",
+        )?;
+
+        let rows = stmt.query_map(
+            params![NaiveDate::from(from), NaiveDate::from(to)],
+            record_from_row,
+        )?;
 
         let records = rows.flatten().collect();
         Ok(records)
@@ -376,7 +752,9 @@ impl Manipulation for SQLiteDatabase {
 mod tests {
     use super::*;
     use crate::datetime::{TaskTime, WorkingDate};
+    use crate::export::{parse_csv, to_csv_row, CSV_HEADER};
     use std::error::Error;
+    use std::io::Write;
     use std::result::Result;
 
     fn prep_db() -> Result<SQLiteDatabase, Box<dyn Error>> {
@@ -386,6 +764,164 @@ mod tests {
         Ok(db)
     }
 
+    #[test]
+    fn test_retry_busy() {
+        let busy = || {
+            rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+                None,
+            )
+        };
+
+        // succeeds once the lock is released
+        let calls = std::cell::Cell::new(0);
+        let result = retry_busy(|| {
+            calls.set(calls.get() + 1);
+            match calls.get() {
+                1 | 2 => Err(busy()),
+                _ => Ok(()),
+            }
+        });
+        assert!(result.is_ok());
+        assert_eq!(calls.get(), 3);
+
+        // gives up after the attempts
+        calls.set(0);
+        let result = retry_busy(|| {
+            calls.set(calls.get() + 1);
+            Err::<(), _>(busy())
+        });
+        assert!(is_busy(&result.unwrap_err()));
+        assert_eq!(calls.get(), BUSY_RETRY_ATTEMPTS);
+
+        // other errors are not retried
+        calls.set(0);
+        let result = retry_busy(|| {
+            calls.set(calls.get() + 1);
+            Err::<(), _>(rusqlite::Error::QueryReturnedNoRows)
+        });
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_transaction_locked() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join(format!("sgt-locked-{}.db", std::process::id()));
+        let db = SQLiteDatabase::open_rwc(&path)?;
+        db.initialize()?;
+        db.conn.busy_timeout(Duration::ZERO)?;
+        let task = Task::new(None, Some("aaa"), None, None, "", false, true);
+
+        // another connection holds the write lock throughout
+        let locker = Connection::open(&path)?;
+        locker.execute_batch("BEGIN IMMEDIATE;")?;
+        let result = db.transaction(|db| db.register_task(&task));
+        let error = result.unwrap_err();
+        assert!(is_locked_error(error.as_ref()));
+        assert!(db.conn.is_autocommit());
+
+        // a reader keeps the transaction from committing, so it is rolled back as a whole
+        locker.execute_batch("ROLLBACK; BEGIN; SELECT count(*) FROM tasks;")?;
+        let result = db.transaction(|db| db.register_task(&task));
+        assert!(is_locked_error(result.unwrap_err().as_ref()));
+        assert!(db.conn.is_autocommit());
+
+        locker.execute_batch("COMMIT;")?;
+        db.transaction(|db| db.register_task(&task))?;
+        assert_eq!(db.tasks()?.len(), 1);
+
+        drop(locker);
+        drop(db);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_busy() {
+        let busy = rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+            None,
+        );
+        assert!(is_busy(&busy));
+        assert!(!is_busy(&rusqlite::Error::QueryReturnedNoRows));
+
+        let boxed: Box<dyn Error> = Box::new(busy);
+        assert!(is_database_error(boxed.as_ref()));
+        assert!(is_locked_error(boxed.as_ref()));
+        let boxed: Box<dyn Error> = "database is locked".into();
+        assert!(!is_database_error(boxed.as_ref()));
+        assert!(!is_locked_error(boxed.as_ref()));
+    }
+
+    #[test]
+    fn test_initialize_schema_version() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        assert_eq!(db.schema_version()?, SCHEMA_VERSION);
+        Ok(())
+    }
+
+    #[test]
+    fn test_integrity_check() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        assert!(db.integrity_check()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate() -> Result<(), Box<dyn Error>> {
+        // schema before migrations were introduced, with a column added without a version
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch(
+            "CREATE TABLE tasks (\
+                id INTEGER PRIMARY KEY AUTOINCREMENT,\
+                level1 TEXT,\
+                level2 TEXT,\
+                level3 TEXT,\
+                description TEXT,\
+                is_break INTEGER,\
+                is_active INTEGER\
+            );\
+            CREATE TABLE records (\
+                id INTEGER PRIMARY KEY AUTOINCREMENT,\
+                task_id INTEGER,\
+                working_date TEXT,\
+                begin TEXT,\
+                end TEXT,\
+                is_break INTEGER,\
+                note TEXT,\
+                FOREIGN KEY(task_id) REFERENCES tasks(id)\
+            );\
+            INSERT INTO tasks VALUES (1, 'aaa', NULL, NULL, '', 0, 1);\
+            INSERT INTO records (task_id, working_date, begin, end, is_break, note) \
+            VALUES (1, '2021-01-01', '2021-01-01T09:00:00', NULL, 0, 'memo');",
+        )?;
+        let db = SQLiteDatabase { conn };
+        assert_eq!(db.schema_version()?, 0);
+
+        db.setup()?;
+        assert_eq!(db.schema_version()?, SCHEMA_VERSION);
+        let mut record = db.records()?.pop().unwrap();
+        assert_eq!(record.note, Some("memo".into()));
+
+        record.tags = vec!["T-1".into()];
+        db.add_record(&record)?;
+        assert_eq!(db.records()?, vec![record]);
+
+        // applied only once
+        db.setup()?;
+        assert_eq!(db.schema_version()?, SCHEMA_VERSION);
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_newer_version() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        db.conn
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)?;
+        assert!(db.setup().is_err());
+        Ok(())
+    }
+
     #[test]
     #[rustfmt::skip]
     fn test_task_register() -> Result<(), Box<dyn Error>> {
@@ -405,6 +941,45 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_task_register_billable() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        let mut task = Task::new(None, Some("aaa"), None, None, "", false, true);
+        task.is_billable = false;
+        db.register_task(&task)?;
+        assert!(!db.get_task(1)?.is_billable);
+
+        task.id = Some(1);
+        task.is_billable = true;
+        db.register_task(&task)?;
+        assert!(db.tasks()?[0].is_billable);
+        Ok(())
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_task_register_duplicate() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        let task1 = Task::new(None, Some("aaa"), Some("xxx"), None, "", false, true);
+        let task2 = Task::new(None, Some("aaa"), Some("xxx"), None, "desc", false, true);
+        db.register_task(&task1)?;
+        db.register_task(&task2)?;
+        assert!(db.register_task(&task1).is_err());
+
+        // updating a task to the same as another
+        let updated = Task::new(Some(2), Some("aaa"), Some("xxx"), None, "", false, true);
+        assert!(db.register_task(&updated).is_err());
+        // updating a task without changing it
+        let updated = Task::new(Some(1), Some("aaa"), Some("xxx"), None, "", true, true);
+        db.register_task(&updated)?;
+
+        // inactive tasks may share the name
+        db.unregister_task(1)?;
+        db.register_task(&task1)?;
+        assert_eq!(db.task_count()?, 3);
+        Ok(())
+    }
+
     #[test]
     #[rustfmt::skip]
     fn test_task_unregister() -> Result<(), Box<dyn Error>> {
@@ -457,6 +1032,150 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_add_record_end_before_begin() -> Result<(), Box<dyn Error>> {
+        let task = Task::new(None, Some("aaa"), Some("xxx"), None, "", false, true);
+        let db = prep_db()?;
+        db.register_task(&task)?;
+
+        let begin = TaskTime::parse("2021-01-01T09:00:00")?;
+        let end = TaskTime::parse("2021-01-01T08:30:00")?;
+        let date = begin.clone().into();
+        let record = TaskRecord::new(None, task, date, begin, Some(end));
+        let err = db.add_record(&record).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "end time 2021-01-01T08:30:00 precedes begin time 2021-01-01T09:00:00"
+        );
+        assert!(db.records()?.is_empty());
+
+        // zero-length records are allowed
+        let mut record = record;
+        record.end = Some(record.begin.clone());
+        db.add_record(&record)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_record_exact_time() -> Result<(), Box<dyn Error>> {
+        let mut task = Task::new(None, Some("aaa"), None, None, "", false, true);
+        let db = prep_db()?;
+        db.register_task(&task)?;
+        task.id = Some(1);
+        let exact_times = |id: u32| {
+            db.conn.query_row(
+                "SELECT begin_exact, end_exact FROM records WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok((
+                        row.get::<_, Option<NaiveDateTime>>(0)?,
+                        row.get::<_, Option<NaiveDateTime>>(1)?,
+                    ))
+                },
+            )
+        };
+
+        // not now
+        let begin = TaskTime::parse("2021-01-01T09:00:00")?;
+        let mut record = TaskRecord::new(None, task.clone(), begin.clone().into(), begin, None);
+        db.add_record(&record)?;
+        assert_eq!(exact_times(1)?, (None, None));
+
+        // ended now, the begin time is unchanged
+        record.id = Some(1);
+        record.end = Some(TaskTime::now());
+        db.add_record(&record)?;
+        let (begin_exact, end_exact) = exact_times(1)?;
+        assert_eq!(begin_exact, None);
+        assert_eq!(end_exact.map(TaskTime::from), record.end);
+
+        // started now
+        let now = TaskTime::now();
+        let record = TaskRecord::new(None, task, now.clone().into(), now.clone(), None);
+        db.add_record(&record)?;
+        let (begin_exact, _) = exact_times(2)?;
+        assert_eq!(begin_exact.map(TaskTime::from), Some(now));
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_record_note() -> Result<(), Box<dyn Error>> {
+        let task = Task::new(None, Some("aaa"), Some("xxx"), None, "", false, true);
+        let db = prep_db()?;
+        db.register_task(&task)?;
+        let task = db.get_task(1)?;
+
+        let begin = TaskTime::parse("2021-01-01T09:00:00")?;
+        let date = begin.clone().into();
+        let mut record = TaskRecord::new(None, task, date, begin, None);
+        record.note = Some("first".into());
+        db.add_record(&record)?;
+
+        let mut record = db.records()?.pop().unwrap();
+        assert_eq!(record.note, Some("first".into()));
+
+        record.note = None;
+        db.add_record(&record)?;
+        assert_eq!(db.records()?, vec![record]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_record_tags() -> Result<(), Box<dyn Error>> {
+        let task = Task::new(None, Some("aaa"), Some("xxx"), None, "", false, true);
+        let db = prep_db()?;
+        db.register_task(&task)?;
+        let task = db.get_task(1)?;
+
+        let begin = TaskTime::parse("2021-01-01T09:00:00")?;
+        let date = begin.clone().into();
+        let mut record = TaskRecord::new(None, task, date, begin, None);
+        record.tags = vec!["T-1".into(), "review".into()];
+        db.add_record(&record)?;
+
+        let mut record = db.records()?.pop().unwrap();
+        assert_eq!(record.tags, vec!["T-1", "review"]);
+
+        record.tags = vec![];
+        db.add_record(&record)?;
+        assert_eq!(db.records()?, vec![record]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_latest_session_record() -> Result<(), Box<dyn Error>> {
+        let task = Task::new(None, Some("aaa"), None, None, "", false, true);
+        let db = prep_db()?;
+        db.register_task(&task)?;
+        let task = db.get_task(1)?;
+        assert_eq!(db.latest_session_record()?, None);
+
+        let time = |s: &str| TaskTime::parse(s).unwrap();
+        let date = WorkingDate::parse("2021-01-01")?;
+        let mut first = TaskRecord::new(
+            None,
+            task.clone(),
+            date.clone(),
+            time("2021-01-01T09:00:00"),
+            Some(time("2021-01-01T10:00:00")),
+        );
+        first.session_id = Some(1);
+        db.add_record(&first)?;
+        let other = TaskRecord::new(
+            None,
+            task.clone(),
+            date.clone(),
+            time("2021-01-01T10:00:00"),
+            Some(time("2021-01-01T11:00:00")),
+        );
+        db.add_record(&other)?;
+
+        let latest = db.latest_session_record()?.unwrap();
+        assert_eq!(latest.id, Some(1));
+        assert_eq!(latest.session_id, Some(1));
+        Ok(())
+    }
+
     #[test]
     fn test_delete_record() -> Result<(), Box<dyn Error>> {
         let task = Task::new(Some(1), Some("aaa"), Some("xxx"), None, "", false, true);
@@ -539,6 +1258,108 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_get_record() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        db.conn.execute(
+            "INSERT INTO tasks (level1, level2, level3, description, is_break, is_active) \
+            VALUES ('a', 'b', 'c', 'd', 0, 1)",
+            [],
+        )?;
+        db.conn.execute(
+            "INSERT INTO records (task_id, working_date, begin, end, is_break, note) \
+            VALUES \
+                (1, '2021-01-01', '2021-01-01 09:00:00', '2021-01-01 12:00:00', 0, NULL),\
+                (1, '2021-01-02', '2021-01-02 09:00:00', NULL, 0, 'memo')",
+            [],
+        )?;
+        let task = Task::new(Some(1), Some("a"), Some("b"), Some("c"), "d", false, true);
+        let mut record = TaskRecord::new(
+            Some(2),
+            task,
+            WorkingDate::parse("2021-01-02")?,
+            TaskTime::parse("2021-01-02T09:00:00")?,
+            None,
+        );
+        record.note = Some("memo".into());
+        assert_eq!(db.get_record(2)?, record);
+        assert!(db.get_record(3).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_counts() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        assert_eq!(db.task_count()?, 0);
+        assert_eq!(db.record_count()?, 0);
+
+        db.conn.execute(
+            "INSERT INTO tasks (level1, level2, level3, description, is_break, is_active) \
+            VALUES ('a', 'b', 'c', 'd', 0, 1), ('e', 'f', 'g', 'h', 0, 0)",
+            [],
+        )?;
+        db.conn.execute(
+            "INSERT INTO records (task_id, working_date, begin, end, is_break) \
+            VALUES \
+                (1, '2021-01-01', '2021-01-01 09:00:00', '2021-01-01 12:00:00', 0),\
+                (2, '2021-01-01', '2021-01-01 13:00:00', '2021-01-01 17:30:00', 0),\
+                (1, '2021-01-02', '2021-01-02 09:00:00', NULL, 0)",
+            [],
+        )?;
+        assert_eq!(db.task_count()?, 2);
+        assert_eq!(db.record_count()?, 3);
+        assert_eq!(db.record_counts_by_task()?, HashMap::from([(1, 2), (2, 1)]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_earliest_date() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        assert_eq!(db.earliest_date()?, None);
+
+        db.conn.execute(
+            "INSERT INTO tasks (level1, level2, level3, description, is_break, is_active) \
+            VALUES ('a', 'b', 'c', 'd', 0, 1)",
+            [],
+        )?;
+        db.conn.execute(
+            "INSERT INTO records (task_id, working_date, begin, end, is_break) \
+            VALUES \
+                (1, '2021-01-02', '2021-01-02 09:00:00', '2021-01-02 12:00:00', 0),\
+                (1, '2020-12-31', '2020-12-31 09:00:00', '2020-12-31 12:00:00', 0)",
+            [],
+        )?;
+        assert_eq!(db.earliest_date()?, Some(WorkingDate::parse("2020-12-31")?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_records_for_task() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        db.conn.execute(
+            "INSERT INTO tasks (level1, level2, level3, description, is_break, is_active) \
+            VALUES ('a', 'b', 'c', 'd', 0, 1), ('e', 'f', 'g', 'h', 0, 1)",
+            [],
+        )?;
+        db.conn.execute(
+            "INSERT INTO records (task_id, working_date, begin, end, is_break) \
+            VALUES \
+                (2, '2021-01-02', '2021-01-02 09:00:00', '2021-01-02 12:00:00', 0),\
+                (1, '2021-01-01', '2021-01-01 13:00:00', '2021-01-01 17:30:00', 0),\
+                (2, '2021-01-01', '2021-01-01 09:00:00', '2021-01-01 12:00:00', 0)",
+            [],
+        )?;
+
+        let ids = db
+            .records_for_task(2)?
+            .iter()
+            .map(|record| record.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![Some(3), Some(1)]);
+        assert!(db.records_for_task(3)?.is_empty());
+        Ok(())
+    }
+
     #[test]
     fn test_get_records_by_date() -> Result<(), Box<dyn Error>> {
         let db = prep_db()?;
@@ -628,6 +1449,149 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_transaction() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        let task = Task::new(None, Some("a"), None, None, "", false, true);
+
+        db.transaction(|db| db.register_task(&task))?;
+        assert_eq!(db.task_count()?, 1);
+
+        // rolled back on error
+        let result = db.transaction(|db| {
+            db.register_task(&Task::new(None, Some("b"), None, None, "", false, true))?;
+            Err::<(), _>("failed".into())
+        });
+        assert!(result.is_err());
+        assert_eq!(db.task_count()?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_round_trip() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        db.conn.execute(
+            "INSERT INTO tasks (level1, level2, level3, description, is_break, is_active) \
+            VALUES ('a', 'b', NULL, 'd, \"quoted\"', 0, 1), ('lunch', NULL, NULL, '', 1, 1)",
+            [],
+        )?;
+        db.conn.execute(
+            "INSERT INTO records (task_id, working_date, begin, end, is_break, note) \
+            VALUES \
+                (1, '2021-01-01', '2021-01-01 09:00:00', '2021-01-01 12:00:00', 0, 'x\ny'),\
+                (2, '2021-01-01', '2021-01-01 12:00:00', '2021-01-01 13:00:00', 1, NULL),\
+                (1, '2021-01-01', '2021-01-01 22:00:00', '2021-01-02 01:30:00', 0, NULL),\
+                (1, '2021-01-02', '2021-01-02 09:00:00', NULL, 0, NULL)",
+            [],
+        )?;
+
+        let mut buffer = vec![];
+        writeln!(buffer, "{}", CSV_HEADER)?;
+        for record in db.records()? {
+            writeln!(buffer, "{}", to_csv_row(&record))?;
+        }
+
+        let imported = prep_db()?;
+        let count = imported.import_records(&parse_csv(&String::from_utf8(buffer)?)?)?;
+        assert_eq!(count, 4);
+
+        let without_ids = |records: Vec<TaskRecord>| {
+            records
+                .into_iter()
+                .map(|mut record| {
+                    record.id = None;
+                    record.task.id = None;
+                    record
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(without_ids(imported.records()?), without_ids(db.records()?));
+        assert_eq!(imported.task_count()?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_round_trip_break_flag_differs() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        db.conn.execute(
+            "INSERT INTO tasks (level1, level2, level3, description, is_break, is_active) \
+            VALUES ('lunch', NULL, NULL, '', 1, 1)",
+            [],
+        )?;
+        db.conn.execute(
+            "INSERT INTO records (task_id, working_date, begin, end, is_break) \
+            VALUES (1, '2021-01-01', '2021-01-01 12:00:00', '2021-01-01 13:00:00', 1)",
+            [],
+        )?;
+
+        let mut buffer = vec![];
+        writeln!(buffer, "{}", CSV_HEADER)?;
+        for record in db.records()? {
+            writeln!(buffer, "{}", to_csv_row(&record))?;
+        }
+
+        // the same task registered without the break flag takes the records
+        let imported = prep_db()?;
+        imported.conn.execute(
+            "INSERT INTO tasks (level1, level2, level3, description, is_break, is_active) \
+            VALUES ('lunch', NULL, NULL, '', 0, 1)",
+            [],
+        )?;
+        let count = imported.import_records(&parse_csv(&String::from_utf8(buffer)?)?)?;
+        assert_eq!(count, 1);
+        assert_eq!(imported.task_count()?, 1);
+        assert_eq!(imported.records_for_task(1)?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize_records() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        db.conn.execute(
+            "INSERT INTO tasks (level1, level2, level3, description, is_break, is_active) \
+            VALUES ('a', 'b', 'c', 'd', 0, 1)",
+            [],
+        )?;
+        db.conn.execute(
+            "INSERT INTO records (task_id, working_date, begin, end, is_break) \
+            VALUES \
+                (1, '2021-01-01', '2021-01-01 09:00:00', '2021-01-01 12:00:00', 0),\
+                (1, '2021-01-01', '2021-01-01 13:00:15', '2021-01-01 17:30:00', 0),\
+                (1, '2021-01-02', '2021-01-02 09:00:00', '2021-01-02 15:00:59.5', 0),\
+                (1, '2021-01-02', '2021-01-02 15:00:30', NULL, 0)",
+            [],
+        )?;
+        let before = db.records()?;
+
+        assert_eq!(db.normalize_records()?, 3);
+        assert_eq!(db.normalize_records()?, 0);
+        assert_eq!(db.records()?, before);
+
+        let stored =
+            db.conn
+                .query_row("SELECT begin, end FROM records WHERE id = 3", [], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?;
+        assert_eq!(
+            stored,
+            ("2021-01-02 09:00:00".into(), "2021-01-02 15:00:00".into())
+        );
+
+        // hidden records are normalized as well
+        db.conn.execute(
+            "UPDATE records SET begin = '2021-01-01 09:00:45', is_hidden = 1 WHERE id = 1",
+            [],
+        )?;
+        assert_eq!(db.normalize_records()?, 1);
+        let begin = db
+            .conn
+            .query_row("SELECT begin FROM records WHERE id = 1", [], |row| {
+                row.get::<_, String>(0)
+            })?;
+        assert_eq!(begin, "2021-01-01 09:00:00");
+        Ok(())
+    }
+
     #[test]
     fn test_current_state_active() -> Result<(), Box<dyn Error>> {
         let db = prep_db()?;
@@ -659,6 +1623,139 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_latest_record() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        let date = WorkingDate::parse("2021-01-01")?;
+        assert_eq!(db.latest_record(&date)?, None);
+
+        db.conn.execute(
+            "INSERT INTO tasks (level1, level2, level3, description, is_break, is_active) \
+            VALUES ('a', 'b', 'c', 'd', 0, 1), ('e', 'f', 'g', 'h', 0, 1)",
+            [],
+        )?;
+        db.conn.execute(
+            "INSERT INTO records (task_id, working_date, begin, end, is_break) \
+            VALUES \
+                (1, '2021-01-01', '2021-01-01 09:00:00', '2021-01-01 12:00:00', 0),\
+                (2, '2021-01-01', '2021-01-01 13:00:00', '2021-01-01 17:00:00', 0),\
+                (1, '2021-01-02', '2021-01-02 09:00:00', NULL, 0)",
+            [],
+        )?;
+        let task = Task::new(Some(2), Some("e"), Some("f"), Some("g"), "h", false, true);
+        let record = TaskRecord::new(
+            Some(2),
+            task,
+            date.clone(),
+            TaskTime::parse("2021-01-01T13:00:00")?,
+            Some(TaskTime::parse("2021-01-01T17:00:00")?),
+        );
+        // completed records are not dropped unlike `current_state`
+        assert_eq!(db.latest_record(&date)?, Some(record));
+        assert_eq!(db.current_state(&date)?, State::Completed);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hide_records_for_task() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        db.conn.execute(
+            "INSERT INTO tasks (level1, level2, level3, description, is_break, is_active) \
+            VALUES ('a', 'b', 'c', 'd', 0, 1), ('e', 'f', 'g', 'h', 0, 1)",
+            [],
+        )?;
+        db.conn.execute(
+            "INSERT INTO records (task_id, working_date, begin, end, is_break) \
+            VALUES \
+                (1, '2021-01-01', '2021-01-01 09:00:00', '2021-01-01 12:00:00', 0),\
+                (2, '2021-01-01', '2021-01-01 13:00:00', NULL, 0)",
+            [],
+        )?;
+
+        assert_eq!(db.hide_records_for_task(2)?, 1);
+        let date = WorkingDate::parse("2021-01-01")?;
+        let ids = |records: Vec<TaskRecord>| records.iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids(db.records()?), vec![Some(1)]);
+        assert_eq!(ids(db.get_records_by_date(&date)?), vec![Some(1)]);
+        assert_eq!(ids(db.get_records_in_period(&date, &date)?), vec![Some(1)]);
+        assert!(db.records_for_task(2)?.is_empty());
+        // the hidden open record is no longer the active one
+        assert_eq!(db.latest_record(&date)?.and_then(|r| r.id), Some(1));
+        assert_eq!(db.latest_open_record()?, None);
+        // left out of counts and lookups as well
+        assert_eq!(db.record_count()?, 1);
+        assert_eq!(db.record_counts_by_task()?, HashMap::from([(1, 1)]));
+        assert!(db.get_record(2).is_err());
+        db.hide_records_for_task(1)?;
+        assert_eq!(db.earliest_date()?, None);
+        // kept in the database
+        let stored: u64 = db
+            .conn
+            .query_row("SELECT count(*) FROM records", [], |row| row.get(0))?;
+        assert_eq!(stored, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_records_same_begin_ordered_by_id() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        db.conn.execute(
+            "INSERT INTO tasks (level1, level2, level3, description, is_break, is_active) \
+            VALUES ('a', 'b', 'c', 'd', 0, 1), ('e', 'f', 'g', 'h', 0, 1)",
+            [],
+        )?;
+        // begin is truncated to minutes, so quick switches share the same begin
+        db.conn.execute(
+            "INSERT INTO records (task_id, working_date, begin, end, is_break) \
+            VALUES \
+                (1, '2021-01-01', '2021-01-01 09:00:00', '2021-01-01 09:00:00', 0),\
+                (2, '2021-01-01', '2021-01-01 09:00:00', '2021-01-01 10:00:00', 0)",
+            [],
+        )?;
+        let date = WorkingDate::parse("2021-01-01")?;
+        let ids = db
+            .get_records_by_date(&date)?
+            .iter()
+            .map(|record| record.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![Some(1), Some(2)]);
+        assert_eq!(
+            db.latest_record(&date)?.and_then(|record| record.id),
+            Some(2)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_latest_open_record() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        assert_eq!(db.latest_open_record()?, None);
+
+        db.conn.execute(
+            "INSERT INTO tasks (level1, level2, level3, description, is_break, is_active) \
+            VALUES ('a', 'b', 'c', 'd', 0, 1), ('e', 'f', 'g', 'h', 0, 1)",
+            [],
+        )?;
+        db.conn.execute(
+            "INSERT INTO records (task_id, working_date, begin, end, is_break) \
+            VALUES \
+                (1, '2021-01-01', '2021-01-01 09:00:00', NULL, 0),\
+                (2, '2021-01-02', '2021-01-02 09:00:00', NULL, 0),\
+                (1, '2021-01-03', '2021-01-03 09:00:00', '2021-01-03 17:30:00', 0)",
+            [],
+        )?;
+        let task = Task::new(Some(2), Some("e"), Some("f"), Some("g"), "h", false, true);
+        let record = TaskRecord::new(
+            Some(2),
+            task,
+            WorkingDate::parse("2021-01-02")?,
+            TaskTime::parse("2021-01-02T09:00:00")?,
+            None,
+        );
+        assert_eq!(db.latest_open_record()?, Some(record));
+        Ok(())
+    }
+
     #[test]
     fn test_current_state_completed() -> Result<(), Box<dyn Error>> {
         let db = prep_db()?;
@@ -1,6 +1,7 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 
-use chrono::TimeDelta;
+use chrono::{TimeDelta, Weekday};
 
 use crate::datetime::{TaskTime, WorkingDate};
 
@@ -17,6 +18,8 @@ pub struct Task {
     pub is_break: bool,
     /// Whether this task is in use or not
     pub is_active: bool,
+    /// Whether time spent on this task is billable to clients
+    pub is_billable: bool,
 }
 
 impl Default for Task {
@@ -49,20 +52,45 @@ impl Task {
             description,
             is_break,
             is_active,
+            is_billable: true,
         }
     }
 
+    /// Whether all task levels and the description are empty.
+    pub fn is_empty(&self) -> bool {
+        self.task
+            .iter()
+            .all(|level| level.as_deref().unwrap_or_default().is_empty())
+            && self.description.is_empty()
+    }
+
     /// Format multi part task names to one string.
     pub fn format_name(&self, sep: &str) -> String {
+        self.format_name_depth(sep, None)
+    }
+
+    /// Format the first `depth` levels of task names to one string. All levels if `None`.
+    pub fn format_name_depth(&self, sep: &str, depth: Option<usize>) -> String {
         let task = self
             .task
             .iter()
+            .take(depth.unwrap_or(usize::MAX))
             .flatten()
             .map(|x| x.to_string())
             .collect::<Vec<String>>()
             .join(sep);
         task
     }
+
+    /// Format multi part task names to one string, or returns `placeholder` if all levels are empty.
+    pub fn format_name_or(&self, sep: &str, placeholder: &str) -> String {
+        let task = self.format_name(sep);
+        if task.is_empty() {
+            placeholder.to_string()
+        } else {
+            task
+        }
+    }
 }
 
 /// Represents a task log.
@@ -78,6 +106,12 @@ pub struct TaskRecord {
     pub begin: TaskTime,
     /// End time
     pub end: Option<TaskTime>,
+    /// Note
+    pub note: Option<String>,
+    /// Freeform labels independent of the task hierarchy
+    pub tags: Vec<String>,
+    /// Id of the first record of a session paused and resumed across records
+    pub session_id: Option<u32>,
 }
 
 impl TaskRecord {
@@ -95,6 +129,9 @@ impl TaskRecord {
             working_date,
             begin,
             end,
+            note: None,
+            tags: vec![],
+            session_id: None,
         }
     }
 
@@ -103,13 +140,122 @@ impl TaskRecord {
         self.task.is_break
     }
 
-    /// Calculates duration.
+    /// Checks whether the record has the tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Checks whether the record ends after the end of its working date (5:00 am of the next day).
+    pub fn crosses_day_boundary(&self) -> bool {
+        self.end
+            .as_ref()
+            .is_some_and(|end| end > &self.working_date.end_of_day())
+    }
+
+    /// Checks whether the record is a duplicate of the other, i.e. another record of the same
+    /// task beginning at the same minute of the same working date.
+    pub fn is_duplicate_of(&self, other: &TaskRecord) -> bool {
+        (self.id.is_none() || self.id != other.id)
+            && self.task.id == other.task.id
+            && self.working_date == other.working_date
+            && self.begin == other.begin
+    }
+
+    /// Calculates duration as the difference of wall-clock times. This is the default.
     pub fn duration(&self) -> TimeDelta {
         let begin = &self.begin;
         self.end
             .as_ref()
             .map_or_else(|| &TaskTime::now() - begin, |end| end - begin)
     }
+
+    /// Calculates duration of an ended record, or `None` for an open record.
+    ///
+    /// Unlike `duration`, open records left on past days are not counted up to now.
+    pub fn duration_or_none(&self) -> Option<TimeDelta> {
+        self.end.as_ref().map(|end| end - &self.begin)
+    }
+
+    /// Checks whether the record is open on the current working date (or later),
+    /// as opposed to left open on a past day.
+    pub fn is_running(&self) -> bool {
+        self.end.is_none() && self.working_date >= WorkingDate::today()
+    }
+
+    /// Calculates actual elapsed time in the effective time zone, accounting for DST transitions.
+    pub fn elapsed(&self) -> TimeDelta {
+        let end = self.end.clone().unwrap_or_else(TaskTime::now);
+        self.begin.elapsed_until(&end)
+    }
+
+    /// Compares records by working date, begin time and id, the order records are stored.
+    pub fn cmp_by_time(&self, other: &TaskRecord) -> Ordering {
+        (&self.working_date, &self.begin, self.id).cmp(&(
+            &other.working_date,
+            &other.begin,
+            other.id,
+        ))
+    }
+
+    /// Compares records by duration.
+    pub fn cmp_by_duration(&self, other: &TaskRecord) -> Ordering {
+        self.duration().cmp(&other.duration())
+    }
+
+    /// Checks whether the time span overlaps with another record. Open records last until now.
+    pub fn overlaps(&self, other: &TaskRecord) -> bool {
+        let self_end = self.end.clone().unwrap_or_else(TaskTime::now);
+        let other_end = other.end.clone().unwrap_or_else(TaskTime::now);
+        self.begin < other_end && other.begin < self_end
+    }
+
+    /// Clips the record to the window from `from` to `to`.
+    ///
+    /// Returns `None` if the record does not overlap the window. Running records last until
+    /// now, and stay open if now is within the window. Open records of past days keep no end.
+    pub fn clip(&self, from: &TaskTime, to: &TaskTime) -> Option<TaskRecord> {
+        let now = TaskTime::now();
+        let end = match &self.end {
+            Some(end) => Some(end),
+            None if self.is_running() => Some(&now),
+            None => None,
+        };
+        if &self.begin >= to || end.is_some_and(|end| end <= from) {
+            return None;
+        }
+
+        let mut record = self.clone();
+        record.begin = self.begin.clone().max(from.clone());
+        record.end = match (&self.end, end) {
+            (_, Some(end)) if end >= to => Some(to.clone()),
+            (Some(end), _) => Some(end.clone()),
+            (None, _) => None,
+        };
+        Some(record)
+    }
+
+    /// Splits the record into two at the specified time.
+    ///
+    /// The first keeps the id and the note and ends at `at`, and the second is a new record
+    /// from `at` to the original end. Returns `None` unless `at` lies strictly within the record.
+    pub fn split_at(&self, at: &TaskTime) -> Option<(TaskRecord, TaskRecord)> {
+        if at <= &self.begin || self.end.as_ref().is_some_and(|end| at >= end) {
+            return None;
+        }
+
+        let mut first = self.clone();
+        first.end = Some(at.clone());
+        let mut second = TaskRecord::new(
+            None,
+            self.task.clone(),
+            self.working_date.clone(),
+            at.clone(),
+            self.end.clone(),
+        );
+        second.tags = self.tags.clone();
+        second.session_id = self.session_id;
+        Some((first, second))
+    }
 }
 
 /// Summary of tasks.
@@ -125,60 +271,395 @@ pub struct TaskSummary {
     pub task_durations: HashMap<String, TimeDelta>,
     /// Collected break times
     pub break_times: Vec<TaskRecord>,
+    /// Total duration of break times
+    pub break_duration: TimeDelta,
+    /// Time within begin..end that is not covered by any record
+    pub gap_duration: TimeDelta,
+    /// Break time that overlaps work records
+    pub break_overlap: TimeDelta,
+    /// Durations by session across paused segments
+    pub session_durations: HashMap<u32, TimeDelta>,
+    /// Total duration of billable tasks
+    pub billable_duration: TimeDelta,
 }
 
-impl From<&[TaskRecord]> for TaskSummary {
-    fn from(value: &[TaskRecord]) -> Self {
-        let work_records = value.iter().filter(|record| !record.is_break());
+impl TaskSummary {
+    /// Summarizes records, grouping task durations by their full name.
+    ///
+    /// Returns `None` if there are no records.
+    pub fn new(value: &[TaskRecord]) -> Option<Self> {
+        Self::with_depth(value, None)
+    }
 
-        let begin = work_records
-            .clone()
-            .map(|record| record.begin.clone())
-            .min()
-            .unwrap();
+    /// Summarizes records, grouping task durations by the first `depth` task levels.
+    ///
+    /// When `depth` is `None`, tasks are grouped by their full name. Returns `None` if there
+    /// are no records. Without work records, such as on a day with breaks only, the durations
+    /// of work are zero and the span is that of the break records.
+    pub fn with_depth(value: &[TaskRecord], depth: Option<usize>) -> Option<Self> {
+        let last = value.last()?;
+        let now = TaskTime::now();
 
-        let end = work_records
-            .clone()
-            .map(|record| record.end.clone())
-            .last()
-            .unwrap();
+        let mut begin: Option<&TaskTime> = None;
+        let mut end: Option<&Option<TaskTime>> = None;
+        let mut span_end: Option<&TaskTime> = None;
+        let mut total_duration = TimeDelta::zero();
+        let mut billable_duration = TimeDelta::zero();
+        let mut task_durations = HashMap::<String, TimeDelta>::new();
+        let mut session_durations = HashMap::<u32, TimeDelta>::new();
+        let mut break_times = vec![];
+        let mut break_duration = TimeDelta::zero();
+        let mut intervals = Vec::with_capacity(value.len());
+        let mut work_intervals = Vec::with_capacity(value.len());
+        let mut break_intervals = vec![];
 
-        let total_duration = work_records
-            .clone()
-            .fold(TimeDelta::zero(), |acc, record| acc + record.duration());
+        for record in value {
+            let record_end = report_end(record, &now);
+            let duration = record_end - &record.begin;
+            intervals.push((&record.begin, record_end));
 
-        let mut task_durations = HashMap::<String, TimeDelta>::new();
+            if record.is_break() {
+                break_times.push(record.clone());
+                break_duration += duration;
+                break_intervals.push((&record.begin, record_end));
+                continue;
+            }
+
+            begin = Some(begin.map_or(&record.begin, |b| b.min(&record.begin)));
+            end = Some(&record.end);
+            span_end = Some(record_end);
+            total_duration += duration;
+            if record.task.is_billable {
+                billable_duration += duration;
+            }
+            work_intervals.push((&record.begin, record_end));
 
-        for record in work_records {
-            let task_name = record.task.format_name("/");
-            let task_duration = record.duration();
-            if task_durations.contains_key(&task_name) {
-                let acc = *task_durations.get(&task_name).unwrap() + task_duration;
-                task_durations.insert(task_name, acc);
-            } else {
-                task_durations.insert(task_name, task_duration);
+            let task_name = record.task.format_name_depth("/", depth);
+            *task_durations
+                .entry(task_name)
+                .or_insert_with(TimeDelta::zero) += duration;
+            if let Some(session_id) = record.session_id {
+                *session_durations
+                    .entry(session_id)
+                    .or_insert_with(TimeDelta::zero) += duration;
             }
         }
 
-        let break_times = value
-            .iter()
-            .filter(|record| record.is_break())
-            .cloned()
-            .collect::<Vec<_>>();
+        let begin = begin
+            .or_else(|| value.iter().map(|record| &record.begin).min())
+            .unwrap()
+            .clone();
+        let end = end.unwrap_or(&last.end).clone();
+        let span_end = span_end.unwrap_or_else(|| report_end(last, &now));
+
+        // merge all records (including breaks) into a covered time within the span,
+        // so that overlapping records never produce a negative gap
+        intervals.sort();
 
-        TaskSummary {
+        let mut covered = TimeDelta::zero();
+        let mut cursor = &begin;
+        for (interval_begin, interval_end) in intervals {
+            let interval_begin = interval_begin.max(cursor);
+            let interval_end = interval_end.min(span_end);
+            if interval_end > interval_begin {
+                covered += interval_end - interval_begin;
+                cursor = interval_end;
+            }
+        }
+        let gap_duration = (span_end - &begin - covered).max(TimeDelta::zero());
+
+        // only breaks falling within work records are counted,
+        // since breaks logged between work records are not in the total anyway;
+        // overlapping breaks are merged so that the same time is deducted once
+        let work_intervals = merge_intervals(work_intervals);
+        let break_intervals = merge_intervals(break_intervals);
+        let mut break_overlap = TimeDelta::zero();
+        for (break_begin, break_end) in &break_intervals {
+            for (work_begin, work_end) in &work_intervals {
+                let overlap_begin = work_begin.max(break_begin);
+                let overlap_end = work_end.min(break_end);
+                if overlap_end > overlap_begin {
+                    break_overlap += *overlap_end - *overlap_begin;
+                }
+            }
+        }
+
+        Some(TaskSummary {
             begin,
             end,
             total_duration,
             task_durations,
             break_times,
+            break_duration,
+            gap_duration,
+            break_overlap,
+            session_durations,
+            billable_duration,
+        })
+    }
+
+    /// Total duration with break time within work records deducted.
+    pub fn net_duration(&self) -> TimeDelta {
+        self.total_duration - self.break_overlap
+    }
+
+    /// Durations by task in descending order of duration. Ties are ordered by task name.
+    pub fn sorted_task_durations(&self) -> Vec<(&str, TimeDelta)> {
+        let mut durations = self
+            .task_durations
+            .iter()
+            .map(|(task, duration)| (task.as_str(), *duration))
+            .collect::<Vec<_>>();
+        durations.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        durations
+    }
+}
+
+/// Parses comma-separated tags, dropping empty and duplicate ones.
+pub fn parse_tags(s: &str) -> Vec<String> {
+    let mut tags: Vec<String> = vec![];
+    for tag in s.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
         }
     }
+    tags
+}
+
+/// Sums durations of work records by tag, in descending order of duration.
+///
+/// A record with several tags counts toward each of them. Break records and untagged records
+/// are ignored.
+pub fn tag_durations(records: &[TaskRecord]) -> Vec<(String, TimeDelta)> {
+    let mut durations = HashMap::<&str, TimeDelta>::new();
+    let now = TaskTime::now();
+    for record in records.iter().filter(|record| !record.is_break()) {
+        let duration = report_end(record, &now) - &record.begin;
+        for tag in &record.tags {
+            *durations.entry(tag).or_insert_with(TimeDelta::zero) += duration;
+        }
+    }
+
+    let mut durations = durations
+        .into_iter()
+        .map(|(tag, duration)| (tag.to_string(), duration))
+        .collect::<Vec<_>>();
+    durations.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    durations
+}
+
+/// Counts sessions of work by task, grouping tasks by the first `depth` levels.
+///
+/// Records of the same task on the same working date belong to one session while the gap
+/// from the end of one to the begin of the next is at most `gap`, even if other tasks are
+/// logged in between.
+pub fn session_counts(
+    records: &[TaskRecord],
+    gap: TimeDelta,
+    depth: Option<usize>,
+) -> HashMap<String, usize> {
+    let now = TaskTime::now();
+    let mut by_task = HashMap::<String, Vec<&TaskRecord>>::new();
+    for record in records.iter().filter(|record| !record.is_break()) {
+        let task_name = record.task.format_name_depth("/", depth);
+        by_task.entry(task_name).or_default().push(record);
+    }
+
+    by_task
+        .into_iter()
+        .map(|(task_name, mut records)| {
+            records.sort_by(|a, b| a.begin.cmp(&b.begin));
+            let mut count = 0;
+            let mut last: Option<(&WorkingDate, &TaskTime)> = None;
+            for record in records {
+                let end = report_end(record, &now);
+                match last {
+                    Some((date, last_end))
+                        if date == &record.working_date && &record.begin - last_end <= gap =>
+                    {
+                        last = Some((date, end.max(last_end)));
+                    }
+                    _ => {
+                        count += 1;
+                        last = Some((&record.working_date, end));
+                    }
+                }
+            }
+            (task_name, count)
+        })
+        .collect()
+}
+
+/// Sums durations of work records by day of the week, from Monday to Sunday.
+///
+/// Each entry also has the number of working dates with work records on the day of the week,
+/// which is the denominator of the average. Break records are ignored.
+pub fn weekday_durations(records: &[TaskRecord]) -> Vec<(Weekday, TimeDelta, usize)> {
+    let mut durations = [TimeDelta::zero(); 7];
+    let mut dates = HashSet::new();
+    let now = TaskTime::now();
+    for record in records.iter().filter(|record| !record.is_break()) {
+        let index = record.working_date.weekday().num_days_from_monday() as usize;
+        durations[index] += report_end(record, &now) - &record.begin;
+        dates.insert(&record.working_date);
+    }
+
+    let mut weekday = Weekday::Mon;
+    let mut result = Vec::with_capacity(7);
+    for duration in durations {
+        let days = dates
+            .iter()
+            .filter(|date| date.weekday() == weekday)
+            .count();
+        result.push((weekday, duration, days));
+        weekday = weekday.succ();
+    }
+    result
+}
+
+/// Most decimal places of percentages, keeping the calculation in minutes within `i64`.
+pub const MAX_PERCENT_PRECISION: u32 = 6;
+
+/// Calculates shares of durations in percent, rounded to `precision` decimal places.
+///
+/// Rounding follows the largest remainder method, so the shares always total exactly 100
+/// (unless all durations are zero). `precision` is capped at `MAX_PERCENT_PRECISION`.
+pub fn percentages(durations: &[TimeDelta], precision: u32) -> Vec<f64> {
+    let total = durations.iter().map(|d| d.num_minutes()).sum::<i64>();
+    if total == 0 {
+        return vec![0.; durations.len()];
+    }
+
+    // count in units of the last decimal place
+    let scale = 10_i64.pow(precision.min(MAX_PERCENT_PRECISION));
+    let units = 100 * scale;
+    let mut shares = durations
+        .iter()
+        .map(|d| {
+            let numerator = d.num_minutes() * units;
+            (numerator / total, numerator % total)
+        })
+        .collect::<Vec<_>>();
+
+    let rest = units - shares.iter().map(|share| share.0).sum::<i64>();
+    let mut order = (0..shares.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| shares[b].1.cmp(&shares[a].1).then(a.cmp(&b)));
+    for &i in order.iter().take(rest as usize) {
+        shares[i].0 += 1;
+    }
+
+    shares
+        .iter()
+        .map(|share| share.0 as f64 / scale as f64)
+        .collect()
+}
+
+/// End time of a record used in reports.
+///
+/// Running records end at `now`, and open records of past days, whose end is unknown,
+/// end at their begin so that they count as zero.
+fn report_end<'a>(record: &'a TaskRecord, now: &'a TaskTime) -> &'a TaskTime {
+    match &record.end {
+        Some(end) => end,
+        None if record.is_running() => now,
+        None => &record.begin,
+    }
+}
+
+/// Merges overlapping or touching intervals into disjoint ones in chronological order.
+fn merge_intervals<'a>(
+    mut intervals: Vec<(&'a TaskTime, &'a TaskTime)>,
+) -> Vec<(&'a TaskTime, &'a TaskTime)> {
+    intervals.sort();
+    let mut merged: Vec<(&TaskTime, &TaskTime)> = vec![];
+    for (interval_begin, interval_end) in intervals {
+        match merged.last_mut() {
+            Some(last) if interval_begin <= last.1 => last.1 = last.1.max(interval_end),
+            _ => merged.push((interval_begin, interval_end)),
+        }
+    }
+    merged
+}
+
+/// Merges consecutive records of the same task whose times touch on the same working date.
+///
+/// `records` must be in chronological order. Returns the records extended to cover the merged
+/// ones, and the ids of the merged records to be deleted. Notes of merged records are joined.
+pub fn merge_adjacent(records: &[TaskRecord]) -> (Vec<TaskRecord>, Vec<u32>) {
+    let mut updated: Vec<TaskRecord> = vec![];
+    let mut deleted = vec![];
+
+    let mut current: Option<TaskRecord> = None;
+    let mut merged = false;
+    for record in records {
+        if let Some(cur) = current.as_mut() {
+            let adjacent = cur.working_date == record.working_date
+                && cur.task.id.is_some()
+                && cur.task.id == record.task.id
+                && cur.end.as_ref() == Some(&record.begin);
+            if adjacent {
+                if let Some(id) = record.id {
+                    cur.end = record.end.clone();
+                    cur.note = match (cur.note.take(), record.note.clone()) {
+                        (Some(a), Some(b)) => Some(format!("{}; {}", a, b)),
+                        (a, b) => a.or(b),
+                    };
+                    for tag in &record.tags {
+                        if !cur.has_tag(tag) {
+                            cur.tags.push(tag.clone());
+                        }
+                    }
+                    deleted.push(id);
+                    merged = true;
+                    continue;
+                }
+            }
+        }
+
+        if let Some(cur) = current.replace(record.clone()) {
+            if merged {
+                updated.push(cur);
+            }
+        }
+        merged = false;
+    }
+    if let Some(cur) = current {
+        if merged {
+            updated.push(cur);
+        }
+    }
+
+    (updated, deleted)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::{record, record_with_id};
+
+    #[test]
+    fn test_task_format_name() {
+        let task = Task::new(None, Some("a"), None, Some("c"), "", false, true);
+        assert_eq!(task.format_name("/"), "a/c");
+        assert_eq!(task.format_name_or("/", "-"), "a/c");
+
+        let task = Task::default();
+        assert_eq!(task.format_name("/"), "");
+        assert_eq!(task.format_name_or("/", "-"), "-");
+
+        let task = Task::new(None, Some("a"), Some("b"), Some("c"), "", false, true);
+        assert_eq!(task.format_name_depth("/", Some(1)), "a");
+        assert_eq!(task.format_name_depth("/", Some(2)), "a/b");
+        assert_eq!(task.format_name_depth("/", None), "a/b/c");
+    }
+
+    #[test]
+    fn test_task_is_empty() {
+        assert!(Task::default().is_empty());
+        assert!(Task::new(None, Some(""), None, None, "", true, true).is_empty());
+        assert!(!Task::new(None, None, None, Some("c"), "", false, true).is_empty());
+        assert!(!Task::new(None, None, None, None, "d", false, true).is_empty());
+    }
 
     #[test]
     fn test_task_summary_time() {
@@ -208,13 +689,613 @@ mod tests {
         let rec3 = TaskRecord::new(None, task1, WorkingDate::from(beg3.clone()), beg3, None);
 
         // time filled
-        let ts1 = TaskSummary::from(&[rec1.clone(), rec2.clone()][..]);
+        let ts1 = TaskSummary::new(&[rec1.clone(), rec2.clone()][..]).unwrap();
         assert_eq!(ts1.begin, beg1.clone());
         assert_eq!(ts1.end, Some(end2));
 
         // no end time
-        let ts2 = TaskSummary::from(&[rec1, rec2, rec3][..]);
+        let ts2 = TaskSummary::new(&[rec1, rec2, rec3][..]).unwrap();
         assert_eq!(ts2.begin, beg1);
         assert_eq!(ts2.end, None);
     }
+
+    #[test]
+    fn test_task_record_duration_or_none() {
+        let task = Task::new(None, Some("a"), None, None, "", false, true);
+        let begin = TaskTime::parse("2021-01-01T09:00:00").unwrap();
+        let mut record = TaskRecord::new(
+            None,
+            task,
+            WorkingDate::from(begin.clone()),
+            begin,
+            Some(TaskTime::parse("2021-01-01T10:30:00").unwrap()),
+        );
+        assert_eq!(record.duration_or_none(), Some(TimeDelta::minutes(90)));
+        assert!(!record.is_running());
+
+        record.end = None;
+        assert_eq!(record.duration_or_none(), None);
+        assert!(!record.is_running());
+
+        record.working_date = WorkingDate::today();
+        assert!(record.is_running());
+    }
+
+    #[test]
+    fn test_task_record_crosses_day_boundary() {
+        let task = Task::new(None, Some("a"), None, None, "", false, true);
+
+        assert!(
+            !record(&task, "2021-01-01T22:00:00", Some("2021-01-02T05:00:00"))
+                .crosses_day_boundary()
+        );
+        assert!(
+            record(&task, "2021-01-01T22:00:00", Some("2021-01-02T08:00:00"))
+                .crosses_day_boundary()
+        );
+        assert!(!record(&task, "2021-01-01T22:00:00", None).crosses_day_boundary());
+    }
+
+    #[test]
+    fn test_task_record_is_duplicate_of() {
+        let task_a = Task::new(Some(1), Some("a"), None, None, "", false, true);
+        let task_b = Task::new(Some(2), Some("b"), None, None, "", false, true);
+        let begin = TaskTime::parse("2021-01-01T09:00:00").unwrap();
+        let date = WorkingDate::from(begin.clone());
+        let existing = TaskRecord::new(Some(1), task_a.clone(), date.clone(), begin.clone(), None);
+
+        let new = TaskRecord::new(None, task_a.clone(), date.clone(), begin.clone(), None);
+        assert!(new.is_duplicate_of(&existing));
+        // the record itself
+        assert!(!existing.is_duplicate_of(&existing));
+
+        let other_task = TaskRecord::new(None, task_b, date.clone(), begin.clone(), None);
+        assert!(!other_task.is_duplicate_of(&existing));
+        let later = TaskRecord::new(
+            None,
+            task_a,
+            date,
+            TaskTime::parse("2021-01-01T09:01:00").unwrap(),
+            None,
+        );
+        assert!(!later.is_duplicate_of(&existing));
+    }
+
+    #[test]
+    fn test_task_summary_open_past_record() {
+        let task = Task::new(None, Some("a"), None, None, "", false, true);
+
+        // the open record counts as zero rather than up to now
+        let records = [
+            record(&task, "2021-01-01T09:00:00", Some("2021-01-01T10:00:00")),
+            record(&task, "2021-01-01T11:00:00", None),
+        ];
+        let ts = TaskSummary::new(&records[..]).unwrap();
+        assert_eq!(ts.total_duration, TimeDelta::hours(1));
+        assert_eq!(ts.end, None);
+        assert_eq!(ts.gap_duration, TimeDelta::hours(1));
+    }
+
+    #[test]
+    fn test_task_summary_breaks_only() {
+        assert!(TaskSummary::new(&[]).is_none());
+
+        let break_task = Task::new(None, Some("lunch"), None, None, "", true, true);
+        let records = [record(
+            &break_task,
+            "2021-01-01T12:00:00",
+            Some("2021-01-01T13:00:00"),
+        )];
+        let ts = TaskSummary::new(&records).unwrap();
+        assert_eq!(ts.begin, records[0].begin);
+        assert_eq!(ts.total_duration, TimeDelta::zero());
+        assert_eq!(ts.net_duration(), TimeDelta::zero());
+        assert!(ts.task_durations.is_empty());
+        assert_eq!(ts.break_duration, TimeDelta::hours(1));
+        assert_eq!(ts.break_overlap, TimeDelta::zero());
+        assert_eq!(ts.gap_duration, TimeDelta::zero());
+    }
+
+    #[test]
+    fn test_task_summary_gap() {
+        let task = Task::new(None, Some("a"), None, None, "", false, true);
+        let break_task = Task::new(None, Some("lunch"), None, None, "", true, true);
+
+        // 09:00-12:00 work, 12:00-13:00 break, 13:30-17:00 work
+        let records = [
+            record(&task, "2021-01-01T09:00:00", Some("2021-01-01T12:00:00")),
+            record(
+                &break_task,
+                "2021-01-01T12:00:00",
+                Some("2021-01-01T13:00:00"),
+            ),
+            record(&task, "2021-01-01T13:30:00", Some("2021-01-01T17:00:00")),
+        ];
+        let ts = TaskSummary::new(&records[..]).unwrap();
+        assert_eq!(ts.gap_duration, TimeDelta::minutes(30));
+        assert_eq!(ts.break_duration, TimeDelta::hours(1));
+
+        // overlapping records
+        let records = [
+            record(&task, "2021-01-01T09:00:00", Some("2021-01-01T12:00:00")),
+            record(&task, "2021-01-01T11:00:00", Some("2021-01-01T13:00:00")),
+            record(&task, "2021-01-01T14:00:00", Some("2021-01-01T15:00:00")),
+        ];
+        let ts = TaskSummary::new(&records[..]).unwrap();
+        assert_eq!(ts.gap_duration, TimeDelta::hours(1));
+
+        // fully overlapping records
+        let records = [
+            record(&task, "2021-01-01T09:00:00", Some("2021-01-01T17:00:00")),
+            record(&task, "2021-01-01T10:00:00", Some("2021-01-01T11:00:00")),
+        ];
+        let ts = TaskSummary::new(&records[..]).unwrap();
+        assert_eq!(ts.gap_duration, TimeDelta::zero());
+    }
+
+    #[test]
+    fn test_task_summary_net_duration() {
+        let task = Task::new(None, Some("a"), None, None, "", false, true);
+        let break_task = Task::new(None, Some("lunch"), None, None, "", true, true);
+
+        // breaks between work records are not deducted
+        let records = [
+            record(&task, "2021-01-01T09:00:00", Some("2021-01-01T12:00:00")),
+            record(
+                &break_task,
+                "2021-01-01T12:00:00",
+                Some("2021-01-01T13:00:00"),
+            ),
+            record(&task, "2021-01-01T13:00:00", Some("2021-01-01T17:00:00")),
+        ];
+        let ts = TaskSummary::new(&records[..]).unwrap();
+        assert_eq!(ts.net_duration(), TimeDelta::hours(7));
+
+        // breaks within a work record are deducted, only for the overlapping part
+        let records = [
+            record(&task, "2021-01-01T09:00:00", Some("2021-01-01T17:00:00")),
+            record(
+                &break_task,
+                "2021-01-01T12:00:00",
+                Some("2021-01-01T13:00:00"),
+            ),
+            record(
+                &break_task,
+                "2021-01-01T16:30:00",
+                Some("2021-01-01T17:30:00"),
+            ),
+        ];
+        let ts = TaskSummary::new(&records[..]).unwrap();
+        assert_eq!(ts.total_duration, TimeDelta::hours(8));
+        assert_eq!(ts.net_duration(), TimeDelta::minutes(6 * 60 + 30));
+
+        // overlapping breaks are deducted once
+        let records = [
+            record(&task, "2021-01-01T09:00:00", Some("2021-01-01T17:00:00")),
+            record(
+                &break_task,
+                "2021-01-01T12:00:00",
+                Some("2021-01-01T13:00:00"),
+            ),
+            record(
+                &break_task,
+                "2021-01-01T12:30:00",
+                Some("2021-01-01T13:30:00"),
+            ),
+        ];
+        let ts = TaskSummary::new(&records[..]).unwrap();
+        assert_eq!(ts.break_overlap, TimeDelta::minutes(90));
+        assert_eq!(ts.net_duration(), TimeDelta::minutes(6 * 60 + 30));
+    }
+
+    #[test]
+    fn test_task_summary_sessions() {
+        let task = Task::new(None, Some("a"), None, None, "", false, true);
+        let other = Task::new(None, Some("b"), None, None, "", false, true);
+        let session_record =
+            |task: &Task, begin: &str, end: &str, session_id: Option<u32>| TaskRecord {
+                session_id,
+                ..record(task, begin, Some(end))
+            };
+
+        // a session paused for another task and resumed
+        let records = [
+            session_record(&task, "2021-01-01T09:00:00", "2021-01-01T10:00:00", Some(1)),
+            session_record(&other, "2021-01-01T10:00:00", "2021-01-01T10:30:00", None),
+            session_record(&task, "2021-01-01T10:30:00", "2021-01-01T12:00:00", Some(1)),
+        ];
+        let ts = TaskSummary::new(&records[..]).unwrap();
+        assert_eq!(
+            ts.session_durations,
+            HashMap::from([(1, TimeDelta::minutes(150))])
+        );
+    }
+
+    #[test]
+    fn test_task_summary_billable() {
+        let task = Task::new(None, Some("a"), None, None, "", false, true);
+        let mut internal = Task::new(None, Some("b"), None, None, "", false, true);
+        internal.is_billable = false;
+        let break_time = Task::new(None, Some("break"), None, None, "", true, true);
+
+        let records = [
+            record(&task, "2021-01-01T09:00:00", Some("2021-01-01T12:00:00")),
+            record(
+                &break_time,
+                "2021-01-01T12:00:00",
+                Some("2021-01-01T13:00:00"),
+            ),
+            record(
+                &internal,
+                "2021-01-01T13:00:00",
+                Some("2021-01-01T14:30:00"),
+            ),
+        ];
+        let ts = TaskSummary::new(&records[..]).unwrap();
+        assert_eq!(ts.billable_duration, TimeDelta::hours(3));
+        assert_eq!(ts.total_duration, TimeDelta::minutes(270));
+    }
+
+    #[test]
+    fn test_task_record_split_at() {
+        let task = Task::new(None, Some("a"), None, None, "", false, true);
+        let time = |s: &str| TaskTime::parse(s).unwrap();
+        let date = WorkingDate::parse("2021-01-01").unwrap();
+        let mut record = TaskRecord::new(
+            Some(1),
+            task.clone(),
+            date.clone(),
+            time("2021-01-01T09:00:00"),
+            Some(time("2021-01-01T12:00:00")),
+        );
+        record.note = Some("memo".into());
+
+        let (first, second) = record.split_at(&time("2021-01-01T10:30:00")).unwrap();
+        assert_eq!(first.id, Some(1));
+        assert_eq!(first.end, Some(time("2021-01-01T10:30:00")));
+        assert_eq!(first.note, Some("memo".into()));
+        assert_eq!(
+            second,
+            TaskRecord::new(
+                None,
+                task.clone(),
+                date.clone(),
+                time("2021-01-01T10:30:00"),
+                Some(time("2021-01-01T12:00:00")),
+            )
+        );
+
+        assert!(record.split_at(&time("2021-01-01T09:00:00")).is_none());
+        assert!(record.split_at(&time("2021-01-01T12:00:00")).is_none());
+        assert!(record.split_at(&time("2021-01-01T13:00:00")).is_none());
+
+        // open records keep the second part open
+        record.end = None;
+        let (_, second) = record.split_at(&time("2021-01-01T13:00:00")).unwrap();
+        assert_eq!(second.end, None);
+    }
+
+    #[test]
+    fn test_task_record_clip() {
+        let task = Task::new(None, Some("a"), None, None, "", false, true);
+        let time = |s: &str| TaskTime::parse(s).unwrap();
+        let from = time("2021-01-01T09:00:00");
+        let to = time("2021-01-01T12:00:00");
+
+        // partly inside
+        let clipped = record_with_id(1, &task, "2021-01-01T08:00:00", Some("2021-01-01T10:00:00"))
+            .clip(&from, &to)
+            .unwrap();
+        assert_eq!(
+            clipped,
+            record_with_id(1, &task, "2021-01-01T09:00:00", Some("2021-01-01T10:00:00"))
+        );
+        let clipped = record_with_id(1, &task, "2021-01-01T11:00:00", Some("2021-01-01T13:00:00"))
+            .clip(&from, &to)
+            .unwrap();
+        assert_eq!(
+            clipped,
+            record_with_id(1, &task, "2021-01-01T11:00:00", Some("2021-01-01T12:00:00"))
+        );
+
+        // covering the window
+        let clipped = record_with_id(1, &task, "2021-01-01T08:00:00", Some("2021-01-01T13:00:00"))
+            .clip(&from, &to)
+            .unwrap();
+        assert_eq!(clipped.duration(), TimeDelta::hours(3));
+
+        // outside
+        assert!(
+            record_with_id(1, &task, "2021-01-01T07:00:00", Some("2021-01-01T09:00:00"))
+                .clip(&from, &to)
+                .is_none()
+        );
+        assert!(
+            record_with_id(1, &task, "2021-01-01T12:00:00", Some("2021-01-01T13:00:00"))
+                .clip(&from, &to)
+                .is_none()
+        );
+
+        // open on a past day
+        let clipped = record_with_id(1, &task, "2021-01-01T10:00:00", None)
+            .clip(&from, &to)
+            .unwrap();
+        assert_eq!(
+            clipped,
+            record_with_id(1, &task, "2021-01-01T10:00:00", None)
+        );
+    }
+
+    #[test]
+    fn test_merge_adjacent() {
+        let task1 = Task::new(Some(1), Some("a"), None, None, "", false, true);
+        let task2 = Task::new(Some(2), Some("b"), None, None, "", false, true);
+
+        let mut records = [
+            record_with_id(
+                1,
+                &task1,
+                "2021-01-01T09:00:00",
+                Some("2021-01-01T10:00:00"),
+            ),
+            record_with_id(
+                2,
+                &task1,
+                "2021-01-01T10:00:00",
+                Some("2021-01-01T11:00:00"),
+            ),
+            record_with_id(
+                3,
+                &task1,
+                "2021-01-01T11:00:00",
+                Some("2021-01-01T12:00:00"),
+            ),
+            record_with_id(
+                4,
+                &task2,
+                "2021-01-01T12:00:00",
+                Some("2021-01-01T13:00:00"),
+            ),
+            // not touching
+            record_with_id(
+                5,
+                &task2,
+                "2021-01-01T13:30:00",
+                Some("2021-01-01T14:00:00"),
+            ),
+            record_with_id(6, &task2, "2021-01-01T14:00:00", None),
+        ];
+        records[0].note = Some("x".into());
+        records[2].note = Some("y".into());
+        records[1].tags = vec!["t".into()];
+        records[2].tags = vec!["t".into(), "u".into()];
+
+        let (updated, deleted) = merge_adjacent(&records);
+        let mut expected1 = record_with_id(
+            1,
+            &task1,
+            "2021-01-01T09:00:00",
+            Some("2021-01-01T12:00:00"),
+        );
+        expected1.note = Some("x; y".into());
+        expected1.tags = vec!["t".into(), "u".into()];
+        let expected2 = record_with_id(5, &task2, "2021-01-01T13:30:00", None);
+        assert_eq!(updated, vec![expected1, expected2]);
+        assert_eq!(deleted, vec![2, 3, 6]);
+
+        let (updated, deleted) = merge_adjacent(&records[3..5]);
+        assert!(updated.is_empty());
+        assert!(deleted.is_empty());
+
+        // tasks without ids are not known to be the same
+        let unsaved1 = Task::new(None, Some("a"), None, None, "", false, true);
+        let unsaved2 = Task::new(None, Some("b"), None, None, "", false, true);
+        let records = [
+            record_with_id(
+                1,
+                &unsaved1,
+                "2021-01-01T09:00:00",
+                Some("2021-01-01T10:00:00"),
+            ),
+            record_with_id(
+                2,
+                &unsaved2,
+                "2021-01-01T10:00:00",
+                Some("2021-01-01T11:00:00"),
+            ),
+        ];
+        let (updated, deleted) = merge_adjacent(&records);
+        assert!(updated.is_empty());
+        assert!(deleted.is_empty());
+    }
+
+    #[test]
+    fn test_task_record_cmp() {
+        let task = Task::new(None, Some("a"), None, None, "", false, true);
+
+        let rec1 = record_with_id(1, &task, "2021-01-01T09:00:00", Some("2021-01-01T12:00:00"));
+        let rec2 = record_with_id(2, &task, "2021-01-01T09:00:00", Some("2021-01-01T10:00:00"));
+        let rec3 = record_with_id(3, &task, "2021-01-01T08:00:00", Some("2021-01-01T08:30:00"));
+
+        let mut records = vec![rec1.clone(), rec2.clone(), rec3.clone()];
+        records.sort_by(TaskRecord::cmp_by_time);
+        assert_eq!(records, vec![rec3.clone(), rec1.clone(), rec2.clone()]);
+
+        records.sort_by(TaskRecord::cmp_by_duration);
+        assert_eq!(records, vec![rec3, rec2, rec1]);
+    }
+
+    #[test]
+    fn test_task_summary_sorted_durations() {
+        let task1 = Task::new(None, Some("a"), None, None, "", false, true);
+        let task2 = Task::new(None, Some("b"), None, None, "", false, true);
+        let task3 = Task::new(None, Some("c"), None, None, "", false, true);
+
+        // durations crossing the 99-hour mark: "100:00" < "99:00" < "9:00" as strings
+        let records = [
+            record(&task1, "2021-01-01T09:00:00", Some("2021-01-01T18:00:00")),
+            record(&task2, "2021-01-02T09:00:00", Some("2021-01-06T12:00:00")),
+            record(&task3, "2021-01-07T09:00:00", Some("2021-01-11T13:00:00")),
+        ];
+        let ts = TaskSummary::new(&records[..]).unwrap();
+        assert_eq!(
+            ts.sorted_task_durations(),
+            vec![
+                ("c", TimeDelta::hours(100)),
+                ("b", TimeDelta::hours(99)),
+                ("a", TimeDelta::hours(9)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        assert_eq!(parse_tags("T-1, review,,T-1 "), vec!["T-1", "review"]);
+        assert!(parse_tags(" , ").is_empty());
+    }
+
+    #[test]
+    fn test_tag_durations() {
+        let work = Task::new(Some(1), Some("a"), None, None, "", false, true);
+        let rest = Task::new(Some(2), Some("b"), None, None, "", true, true);
+        let tagged_record = |task: &Task, begin: &str, end: &str, tags: &[&str]| TaskRecord {
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..record(task, begin, Some(end))
+        };
+
+        let records = [
+            tagged_record(&work, "2021-01-01T09:00:00", "2021-01-01T10:00:00", &["x"]),
+            tagged_record(
+                &work,
+                "2021-01-01T10:00:00",
+                "2021-01-01T12:00:00",
+                &["x", "y"],
+            ),
+            tagged_record(&rest, "2021-01-01T12:00:00", "2021-01-01T13:00:00", &["y"]),
+            tagged_record(&work, "2021-01-01T13:00:00", "2021-01-01T14:00:00", &[]),
+        ];
+        assert_eq!(
+            tag_durations(&records),
+            vec![
+                ("x".to_string(), TimeDelta::hours(3)),
+                ("y".to_string(), TimeDelta::hours(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_session_counts() {
+        let a = Task::new(Some(1), Some("a"), Some("x"), None, "", false, true);
+        let b = Task::new(Some(2), Some("b"), None, None, "", false, true);
+        let rest = Task::new(Some(3), Some("c"), None, None, "", true, true);
+
+        let records = [
+            record(&a, "2021-01-01T09:00:00", Some("2021-01-01T10:00:00")),
+            record(&b, "2021-01-01T10:00:00", Some("2021-01-01T10:05:00")),
+            record(&a, "2021-01-01T10:05:00", Some("2021-01-01T11:00:00")),
+            record(&rest, "2021-01-01T11:00:00", Some("2021-01-01T12:00:00")),
+            record(&a, "2021-01-01T12:00:00", Some("2021-01-01T13:00:00")),
+            record(&a, "2021-01-02T09:00:00", Some("2021-01-02T10:00:00")),
+        ];
+
+        let counts = session_counts(&records, TimeDelta::zero(), None);
+        assert_eq!(counts["a/x"], 4);
+        assert_eq!(counts["b"], 1);
+        assert!(!counts.contains_key("c"));
+
+        let counts = session_counts(&records, TimeDelta::minutes(5), None);
+        assert_eq!(counts["a/x"], 3);
+
+        // never across working dates
+        let counts = session_counts(&records, TimeDelta::hours(1), Some(1));
+        assert_eq!(counts["a"], 2);
+    }
+
+    #[test]
+    fn test_weekday_durations() {
+        let work = Task::new(Some(1), Some("a"), None, None, "", false, true);
+        let rest = Task::new(Some(2), Some("b"), None, None, "", true, true);
+
+        // 2021-01-04 and 2021-01-11 are Mondays
+        let records = [
+            record(&work, "2021-01-04T09:00:00", Some("2021-01-04T12:00:00")),
+            record(&rest, "2021-01-04T12:00:00", Some("2021-01-04T13:00:00")),
+            record(&work, "2021-01-04T13:00:00", Some("2021-01-04T14:00:00")),
+            record(&work, "2021-01-06T09:00:00", Some("2021-01-06T11:00:00")),
+            record(&work, "2021-01-11T09:00:00", Some("2021-01-11T11:00:00")),
+            // belongs to Saturday until 5:00 am
+            record(&work, "2021-01-10T01:00:00", Some("2021-01-10T02:00:00")),
+        ];
+        let durations = weekday_durations(&records);
+        assert_eq!(durations.len(), 7);
+        assert_eq!(durations[0], (Weekday::Mon, TimeDelta::hours(6), 2));
+        assert_eq!(durations[1], (Weekday::Tue, TimeDelta::zero(), 0));
+        assert_eq!(durations[2], (Weekday::Wed, TimeDelta::hours(2), 1));
+        assert_eq!(durations[5], (Weekday::Sat, TimeDelta::hours(1), 1));
+        assert_eq!(durations[6], (Weekday::Sun, TimeDelta::zero(), 0));
+    }
+
+    #[test]
+    fn test_percentages() {
+        let durations = [
+            TimeDelta::minutes(60),
+            TimeDelta::minutes(60),
+            TimeDelta::minutes(60),
+        ];
+        assert_eq!(percentages(&durations, 0), vec![34., 33., 33.]);
+        assert_eq!(percentages(&durations, 1), vec![33.4, 33.3, 33.3]);
+
+        let durations = [
+            TimeDelta::minutes(200),
+            TimeDelta::minutes(150),
+            TimeDelta::minutes(40),
+            TimeDelta::minutes(10),
+        ];
+        let result = percentages(&durations, 0);
+        assert_eq!(result, vec![50., 38., 10., 2.]);
+        assert_eq!(result.iter().sum::<f64>(), 100.);
+
+        assert_eq!(percentages(&[TimeDelta::zero()], 1), vec![0.]);
+        assert!(percentages(&[], 1).is_empty());
+
+        // too many decimal places would overflow
+        let durations = [TimeDelta::hours(1000), TimeDelta::hours(2000)];
+        assert_eq!(percentages(&durations, 25), percentages(&durations, 6));
+    }
+
+    #[test]
+    fn test_task_summary_with_depth() {
+        let task1 = Task::new(None, Some("a"), Some("x"), None, "", false, true);
+        let beg1 = TaskTime::parse("2021-01-01T10:00:00").unwrap();
+        let end1 = TaskTime::parse("2021-01-01T12:00:00").unwrap();
+        let rec1 = TaskRecord::new(
+            None,
+            task1,
+            WorkingDate::from(beg1.clone()),
+            beg1,
+            Some(end1),
+        );
+
+        let task2 = Task::new(None, Some("a"), Some("y"), None, "", false, true);
+        let beg2 = TaskTime::parse("2021-01-01T13:00:00").unwrap();
+        let end2 = TaskTime::parse("2021-01-01T14:00:00").unwrap();
+        let rec2 = TaskRecord::new(
+            None,
+            task2,
+            WorkingDate::from(beg2.clone()),
+            beg2,
+            Some(end2),
+        );
+
+        let records = [rec1, rec2];
+
+        let ts = TaskSummary::new(&records[..]).unwrap();
+        assert_eq!(ts.task_durations.len(), 2);
+        assert_eq!(ts.task_durations["a/x"], TimeDelta::hours(2));
+        assert_eq!(ts.task_durations["a/y"], TimeDelta::hours(1));
+
+        let ts = TaskSummary::with_depth(&records, Some(1)).unwrap();
+        assert_eq!(ts.task_durations.len(), 1);
+        assert_eq!(ts.task_durations["a"], TimeDelta::hours(3));
+    }
 }
@@ -0,0 +1,24 @@
+//! Fixtures shared by unit tests.
+
+use crate::datetime::{TaskTime, WorkingDate};
+use crate::task::{Task, TaskRecord};
+
+/// Creates an unsaved record of `task` on the working date of `begin`.
+pub(crate) fn record(task: &Task, begin: &str, end: Option<&str>) -> TaskRecord {
+    let begin = TaskTime::parse(begin).unwrap();
+    TaskRecord::new(
+        None,
+        task.clone(),
+        WorkingDate::from(begin.clone()),
+        begin,
+        end.map(|end| TaskTime::parse(end).unwrap()),
+    )
+}
+
+/// Creates a record with `id` of `task` on the working date of `begin`.
+pub(crate) fn record_with_id(id: u32, task: &Task, begin: &str, end: Option<&str>) -> TaskRecord {
+    TaskRecord {
+        id: Some(id),
+        ..record(task, begin, end)
+    }
+}
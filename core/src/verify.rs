@@ -0,0 +1,135 @@
+use crate::datetime::WorkingDate;
+use crate::task::TaskRecord;
+
+/// Anomaly found in a record.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Anomaly {
+    /// End time is earlier than begin time.
+    EndBeforeBegin,
+    /// Time span overlaps with another record (id) on the same working date.
+    Overlap(Option<u32>),
+    /// Record is still open on a past working date.
+    OpenInPast,
+    /// Referenced task does not exist.
+    MissingTask,
+    /// Referenced task is unregistered.
+    InactiveTask,
+    /// Record ends after the end of its working date (5:00 am of the next day).
+    CrossesDayBoundary,
+}
+
+impl Anomaly {
+    /// Whether the anomaly can be legitimate, such as an overnight shift.
+    pub fn is_warning(&self) -> bool {
+        matches!(self, Anomaly::CrossesDayBoundary)
+    }
+}
+
+impl std::fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Anomaly::EndBeforeBegin => write!(f, "end time is earlier than begin time"),
+            Anomaly::Overlap(Some(id)) => write!(f, "overlaps with record {}", id),
+            Anomaly::Overlap(None) => write!(f, "overlaps with another record"),
+            Anomaly::OpenInPast => write!(f, "not ended on a past date"),
+            Anomaly::MissingTask => write!(f, "task does not exist"),
+            Anomaly::InactiveTask => write!(f, "task is unregistered"),
+            Anomaly::CrossesDayBoundary => write!(f, "ends after the end of the working date"),
+        }
+    }
+}
+
+/// Anomaly with the record it was found in.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Issue {
+    /// Record
+    pub record: TaskRecord,
+    /// Anomaly
+    pub anomaly: Anomaly,
+}
+
+/// Scans records and collects anomalies.
+///
+/// Records are expected to be ordered by working date and begin time.
+pub fn find_issues(records: &[TaskRecord], today: &WorkingDate) -> Vec<Issue> {
+    let mut issues = vec![];
+    let mut push = |record: &TaskRecord, anomaly: Anomaly| {
+        issues.push(Issue {
+            record: record.clone(),
+            anomaly,
+        })
+    };
+
+    for (i, record) in records.iter().enumerate() {
+        if let Some(end) = &record.end {
+            if end < &record.begin {
+                push(record, Anomaly::EndBeforeBegin);
+            }
+            if record.crosses_day_boundary() {
+                push(record, Anomaly::CrossesDayBoundary);
+            }
+        } else if &record.working_date < today {
+            push(record, Anomaly::OpenInPast);
+        }
+
+        if record.task.id.is_none() {
+            push(record, Anomaly::MissingTask);
+        } else if !record.task.is_active {
+            push(record, Anomaly::InactiveTask);
+        }
+
+        let later_records = records[i + 1..]
+            .iter()
+            .take_while(|other| other.working_date == record.working_date);
+        for other in later_records {
+            if record.overlaps(other) {
+                push(record, Anomaly::Overlap(other.id));
+            }
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::Task;
+    use crate::test_util::record_with_id;
+
+    #[test]
+    fn test_find_issues() {
+        let task = Task::new(Some(1), Some("a"), None, None, "", false, true);
+        let inactive = Task::new(Some(2), Some("b"), None, None, "", false, false);
+        let missing = Task::new(None, None, None, None, "", false, false);
+        let today = WorkingDate::parse("2021-01-03").unwrap();
+
+        let records = [
+            record_with_id(7, &task, "2020-12-31T22:00:00", Some("2021-01-01T08:00:00")),
+            record_with_id(1, &task, "2021-01-01T09:00:00", Some("2021-01-01T12:00:00")),
+            record_with_id(2, &task, "2021-01-01T11:00:00", Some("2021-01-01T13:00:00")),
+            record_with_id(3, &task, "2021-01-01T15:00:00", Some("2021-01-01T14:00:00")),
+            record_with_id(
+                4,
+                &inactive,
+                "2021-01-02T09:00:00",
+                Some("2021-01-02T10:00:00"),
+            ),
+            record_with_id(5, &missing, "2021-01-02T10:00:00", None),
+            record_with_id(6, &task, "2021-01-03T09:00:00", None),
+        ];
+
+        let result = find_issues(&records, &today)
+            .into_iter()
+            .map(|issue| (issue.record.id.unwrap(), issue.anomaly))
+            .collect::<Vec<_>>();
+        let expected = vec![
+            (7, Anomaly::CrossesDayBoundary),
+            (1, Anomaly::Overlap(Some(2))),
+            (3, Anomaly::EndBeforeBegin),
+            (4, Anomaly::InactiveTask),
+            (5, Anomaly::OpenInPast),
+            (5, Anomaly::MissingTask),
+        ];
+        assert_eq!(result, expected);
+    }
+}
@@ -1,21 +1,39 @@
 use std::error::Error;
 use std::io::Write;
+use std::path::PathBuf;
 
 use directories::ProjectDirs;
 
 use shigotolog::sqlite_db::SQLiteDatabase;
 
-/// Creates a database.
-pub fn setup_db(
-    app_name: &str,
-    mut writer: impl Write,
-) -> Result<std::path::PathBuf, Box<dyn Error>> {
-    let proj_dirs = ProjectDirs::from("", "", app_name).ok_or("Unable to crate data directory")?;
-    let data_dir = proj_dirs.data_dir();
-    let db_path = &data_dir.join(format!("{}.db", app_name));
+/// Environment variable to override the database path.
+const DB_PATH_ENV: &str = "SGT_DB_PATH";
+
+/// Resolves the database path without touching the file system.
+///
+/// `SGT_DB_PATH` takes precedence over the data directory of the app.
+pub fn db_path(app_name: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let path = match std::env::var_os(DB_PATH_ENV) {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let proj_dirs =
+                ProjectDirs::from("", "", app_name).ok_or("Unable to crate data directory")?;
+            proj_dirs.data_dir().join(format!("{}.db", app_name))
+        }
+    };
+    Ok(path)
+}
+
+/// Creates a database, or migrates an existing one to the current schema.
+///
+/// The database is placed in the data directory of the app unless `SGT_DB_PATH` is set.
+pub fn setup_db(app_name: &str, mut writer: impl Write) -> Result<PathBuf, Box<dyn Error>> {
+    let db_path = &db_path(app_name)?;
 
-    if !data_dir.exists() {
-        std::fs::create_dir_all(data_dir)?;
+    if let Some(data_dir) = db_path.parent() {
+        if !data_dir.as_os_str().is_empty() && !data_dir.exists() {
+            std::fs::create_dir_all(data_dir)?;
+        }
     }
 
     if !db_path.exists() {
@@ -28,6 +46,9 @@ pub fn setup_db(
         )?;
 
         initialize_tables(&db, &mut writer)?;
+    } else {
+        // opening for writing applies pending schema migrations
+        SQLiteDatabase::open_rw(db_path)?;
     }
 
     Ok(db_path.to_owned())
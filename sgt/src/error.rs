@@ -0,0 +1,172 @@
+use std::error::Error;
+use std::fmt;
+use std::num::ParseIntError;
+
+use clap::ValueEnum;
+use inquire::InquireError;
+
+use shigotolog::datetime::ParseError;
+use shigotolog::sqlite_db::{is_database_error, is_locked_error};
+
+use crate::format::json_string;
+
+/// Exit status of `verify` and `doctor` when they find issues, apart from those of errors.
+pub const ISSUES_FOUND_EXIT_CODE: i32 = 6;
+
+/// Format of errors printed on failure.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    /// Plain message
+    #[default]
+    Text,
+    /// JSON object with the message and the kind of the error
+    Json,
+}
+
+/// Kind of failure, telling scripts why a command failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Unexpected failure such as I/O, database or prompt errors
+    Other,
+    /// Malformed date, time or number
+    Parse,
+    /// Input rejected by the command
+    Validation,
+    /// Database without tables
+    NotInitialized,
+    /// Database locked by another connection
+    DbLocked,
+}
+
+impl ErrorKind {
+    /// Classifies an error returned from a command.
+    ///
+    /// Errors made of a message only are rejections of input, as commands report them that way.
+    /// Errors of known types other than these, such as those of prompts, are unexpected.
+    pub fn of(error: &(dyn Error + 'static)) -> Self {
+        if let Some(error) = error.downcast_ref::<CliError>() {
+            error.kind
+        } else if is_locked_error(error) {
+            ErrorKind::DbLocked
+        } else if error.is::<ParseError>()
+            || error.is::<chrono::ParseError>()
+            || error.is::<ParseIntError>()
+        {
+            ErrorKind::Parse
+        } else if error.is::<std::io::Error>()
+            || error.is::<InquireError>()
+            || is_database_error(error)
+        {
+            ErrorKind::Other
+        } else {
+            ErrorKind::Validation
+        }
+    }
+
+    /// Name of the kind in JSON output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::Other => "other",
+            ErrorKind::Parse => "parse",
+            ErrorKind::Validation => "validation",
+            ErrorKind::NotInitialized => "not_initialized",
+            ErrorKind::DbLocked => "db_locked",
+        }
+    }
+
+    /// Exit status of the process.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ErrorKind::Other => 1,
+            ErrorKind::Parse => 2,
+            ErrorKind::Validation => 3,
+            ErrorKind::NotInitialized => 4,
+            ErrorKind::DbLocked => 5,
+        }
+    }
+}
+
+/// Error with an explicit kind.
+pub struct CliError {
+    kind: ErrorKind,
+    message: String,
+}
+
+impl CliError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        CliError {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// Parse error naming the rejected input, e.g. ``invalid duration `xx`: invalid format``.
+    pub fn invalid(what: &str, input: &str, error: impl fmt::Display) -> Self {
+        Self::new(
+            ErrorKind::Parse,
+            format!("invalid {} `{}`: {}", what, input, error),
+        )
+    }
+}
+
+// printed the same way as errors made of a message
+impl fmt::Debug for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.message)
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for CliError {}
+
+/// Formats an error as a single-line JSON object: `{"error": "...", "kind": "..."}`.
+pub fn to_json(error: &(dyn Error + 'static)) -> String {
+    format!(
+        r#"{{"error": {}, "kind": "{}"}}"#,
+        json_string(&error.to_string()),
+        ErrorKind::of(error).name()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use shigotolog::datetime::{parse_duration, parse_weekdays};
+
+    #[test]
+    fn test_error_kind_of() {
+        let error: Box<dyn Error> = parse_duration("xx").unwrap_err();
+        assert_eq!(ErrorKind::of(error.as_ref()), ErrorKind::Parse);
+        let error: Box<dyn Error> = parse_weekdays("xyz").unwrap_err();
+        assert_eq!(ErrorKind::of(error.as_ref()), ErrorKind::Parse);
+
+        let error: Box<dyn Error> = "Today already has records".into();
+        assert_eq!(ErrorKind::of(error.as_ref()), ErrorKind::Validation);
+        assert_eq!(ErrorKind::of(error.as_ref()).exit_code(), 3);
+
+        let error: Box<dyn Error> = InquireError::NotTTY.into();
+        assert_eq!(ErrorKind::of(error.as_ref()), ErrorKind::Other);
+    }
+
+    #[test]
+    fn test_cli_error_invalid() {
+        // wrapping a parse error in a message keeps the parse exit code
+        let error: Box<dyn Error> = parse_duration("xx")
+            .map_err(|e| CliError::invalid("duration", "xx", e))
+            .unwrap_err()
+            .into();
+        assert_eq!(error.to_string(), "invalid duration `xx`: invalid format");
+        assert_eq!(ErrorKind::of(error.as_ref()), ErrorKind::Parse);
+        assert_eq!(ErrorKind::of(error.as_ref()).exit_code(), 2);
+        assert_eq!(
+            to_json(error.as_ref()),
+            r#"{"error": "invalid duration `xx`: invalid format", "kind": "parse"}"#
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use std::error::Error;
+use std::io::Write;
+
+use clap::ValueEnum;
+
+use shigotolog::export::{to_csv_row, CSV_HEADER};
+use shigotolog::task::TaskRecord;
+
+use crate::table;
+
+/// Format of records printed by commands.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum RecordFormat {
+    /// Table for reading
+    #[default]
+    Table,
+    /// CSV in the format of `export`
+    Csv,
+    /// JSON array of records
+    Json,
+}
+
+/// Writes records in a format.
+pub trait RecordFormatter {
+    fn write_records(
+        &self,
+        records: &[TaskRecord],
+        writer: &mut dyn Write,
+    ) -> Result<(), Box<dyn Error>>;
+}
+
+/// Records table, without a line break at the end like other tables.
+#[derive(Debug, Default)]
+pub struct TableFormatter {
+    /// Print record ids in the first column
+    pub show_id: bool,
+    /// Number of task levels to show, all levels if `None`
+    pub depth: Option<usize>,
+    /// Insert rows for unlogged time between records
+    pub show_gaps: bool,
+    /// Show times as stored (`YYYY-MM-DDTHH:MM:SS`)
+    pub raw: bool,
+}
+
+impl RecordFormatter for TableFormatter {
+    fn write_records(
+        &self,
+        records: &[TaskRecord],
+        writer: &mut dyn Write,
+    ) -> Result<(), Box<dyn Error>> {
+        let table = table::record_list_with_options(
+            records,
+            self.show_id,
+            self.depth,
+            self.show_gaps,
+            self.raw,
+        );
+        write!(writer, "{}", table)?;
+        Ok(())
+    }
+}
+
+/// CSV lines, which can be imported by `import`.
+#[derive(Debug)]
+pub struct CsvFormatter {
+    /// Write the header line first
+    pub header: bool,
+}
+
+impl RecordFormatter for CsvFormatter {
+    fn write_records(
+        &self,
+        records: &[TaskRecord],
+        writer: &mut dyn Write,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.header {
+            writeln!(writer, "{}", CSV_HEADER)?;
+        }
+        for record in records {
+            writeln!(writer, "{}", to_csv_row(record))?;
+        }
+        Ok(())
+    }
+}
+
+/// JSON array with an object per line for each record.
+#[derive(Debug)]
+pub struct JsonFormatter;
+
+impl RecordFormatter for JsonFormatter {
+    fn write_records(
+        &self,
+        records: &[TaskRecord],
+        writer: &mut dyn Write,
+    ) -> Result<(), Box<dyn Error>> {
+        writeln!(writer, "[")?;
+        for (i, record) in records.iter().enumerate() {
+            let separator = if i + 1 < records.len() { "," } else { "" };
+            writeln!(writer, "  {}{}", to_json_object(record), separator)?;
+        }
+        writeln!(writer, "]")?;
+        Ok(())
+    }
+}
+
+/// Formats a record as a single-line JSON object. Times are in `YYYY-MM-DDTHH:MM:SS`.
+fn to_json_object(record: &TaskRecord) -> String {
+    let optional = |value: Option<String>| value.map_or("null".into(), |s| json_string(&s));
+    let levels = record
+        .task
+        .task
+        .iter()
+        .map(|level| optional(level.clone()))
+        .collect::<Vec<_>>();
+    let tags = record
+        .tags
+        .iter()
+        .map(|tag| json_string(tag))
+        .collect::<Vec<_>>();
+    format!(
+        concat!(
+            r#"{{"id": {}, "date": {}, "begin": {}, "end": {}, "task": [{}], "#,
+            r#""description": {}, "is_break": {}, "note": {}, "tags": [{}]}}"#
+        ),
+        record.id.map_or("null".into(), |id| id.to_string()),
+        json_string(&record.working_date.to_string()),
+        json_string(&record.begin.to_string()),
+        optional(record.end.as_ref().map(|end| end.to_string())),
+        levels.join(", "),
+        json_string(&record.task.description),
+        record.task.is_break,
+        optional(record.note.clone()),
+        tags.join(", "),
+    )
+}
+
+/// Quotes a string as a JSON string literal.
+pub fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -1,4 +1,7 @@
 pub mod database;
+pub mod error;
+pub mod format;
+pub mod output;
 pub mod prompt;
 pub mod subcommand;
 pub mod table;
@@ -1,12 +1,31 @@
-use std::io::{stderr, stdout};
+use std::io::{sink, stderr, stdin, stdout, IsTerminal, Write};
+use std::path::PathBuf;
 
-use clap::{Args, Parser, Subcommand};
+use chrono::{TimeDelta, Weekday};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
+use shigotolog::datetime::{
+    parse_duration, parse_utc_offset, parse_weekdays, set_display_format, set_fixed_offset,
+    WorkingDate, DEFAULT_WEEKEND,
+};
+use shigotolog::repository::Manipulation;
 use shigotolog::sqlite_db::SQLiteDatabase;
+use shigotolog::task::MAX_PERCENT_PRECISION;
 
-use sgt::database::setup_db;
+use sgt::database::{db_path, setup_db};
+use sgt::error::{self, CliError, ErrorFormat, ErrorKind, ISSUES_FOUND_EXIT_CODE};
+use sgt::format::RecordFormat;
+use sgt::output::{is_stdout, AtomicFile};
 use sgt::prompt;
 use sgt::subcommand;
+use sgt::subcommand::log::LogOptions;
+use sgt::subcommand::summary::SummaryOptions;
+use sgt::subcommand::task::ls::TaskSort;
+use sgt::table::{
+    set_color, set_language, set_max_width, set_table_style, set_ticket_url_template, Language,
+    TableStyle,
+};
+use sgt::util::warn_open_record;
 
 /// ShigotoLog CLI
 #[derive(Debug, Parser)]
@@ -16,12 +35,42 @@ use sgt::subcommand;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Table border style
+    #[arg(long, global = true, value_name = "STYLE", default_value = "sharp")]
+    style: TableStyle,
+    /// Date display format in strftime syntax (e.g. %m/%d)
+    #[arg(long, global = true, value_name = "FORMAT")]
+    date_format: Option<String>,
+    /// Time display format in strftime syntax (e.g. "%-I:%M %p")
+    #[arg(long, global = true, value_name = "FORMAT")]
+    time_format: Option<String>,
+    /// Maximum table width; long cells are truncated [default: terminal width]
+    #[arg(long, global = true, value_name = "N")]
+    max_width: Option<usize>,
+    /// Print tables at full width, without combining or truncating columns
+    #[arg(long, global = true, conflicts_with("max_width"))]
+    wide: bool,
+    /// Disable colors (also disabled by the NO_COLOR environment variable)
+    #[arg(long, global = true)]
+    no_color: bool,
+    /// Do not print records after changing them
+    #[arg(short, long, global = true)]
+    quiet: bool,
+    /// Language of table headers and labels [default: en]
+    #[arg(long, global = true, value_name = "LANG")]
+    lang: Option<Language>,
+    /// Days of the week not expected to be worked [default: sat,sun]
+    #[arg(long, global = true, value_name = "DAYS")]
+    weekend: Option<String>,
+    /// Format of errors; failures exit with a code by the kind of error
+    #[arg(long, global = true, value_name = "FORMAT", default_value = "text")]
+    error_format: ErrorFormat,
 }
 
 #[derive(Debug, Subcommand)]
 enum Commands {
     /// Initialize database
-    Init,
+    Init(InitArgs),
     /// Manipulate a task
     #[command(flatten_help = true)]
     Task(TaskArgs),
@@ -31,10 +80,55 @@ enum Commands {
     /// End task
     #[command(visible_alias = "e")]
     End(EndArgs),
+    /// Start the work task as the first record of today
+    Arrive(ArriveArgs),
+    /// End the task running today now
+    Leave,
+    /// End the active task to resume it later as the same session
+    Pause,
+    /// Resume the last paused task
+    Resume,
+    /// Add a record lasting the specified duration
+    LogDuration(LogDurationArgs),
     /// Fix time
     Fix(FixArgs),
+    /// Edit a record by id
+    Edit(EditArgs),
+    /// Split a record into two at the specified time
+    Split(SplitArgs),
+    /// Merge consecutive records of the same task
+    MergeRecords(MergeRecordsArgs),
+    /// Copy records of a day to another day
+    CopyDay(CopyDayArgs),
     /// Print records
     Log(LogArgs),
+    /// Print durations by task
+    Summary(SummaryArgs),
+    /// Export records as CSV
+    Export(ExportArgs),
+    /// Import records from CSV made by export
+    Import(ImportArgs),
+    /// Print the active task and today's worked time
+    Status(StatusArgs),
+    /// Print the first begin and the last end of each day
+    Attendance(AttendanceArgs),
+    /// Print clock-in, clock-out, break and net worked time of each day of a month
+    Timesheet(TimesheetArgs),
+    /// Check records for anomalies, exiting with status 6 if any are found
+    Verify,
+    /// Diagnose the database without changing it, exiting with status 6 on issues
+    Doctor,
+    /// Print the path of the database file
+    Path,
+    /// Truncate seconds of stored times
+    Normalize,
+}
+
+#[derive(Debug, Args)]
+struct InitArgs {
+    /// Initialize without confirmation, deleting all existing data
+    #[arg(short, long)]
+    yes: bool,
 }
 
 #[derive(Debug, Args)]
@@ -49,30 +143,119 @@ enum TaskCommands {
     /// Register or update a task
     Register,
     /// Unregister a task
-    Unregister,
+    Unregister(UnregisterArgs),
+    /// Reactivate an unregistered task
+    Reactivate,
     /// List active tasks
     Ls(LsArgs),
+    /// List distinct task names at a level
+    Levels(LevelsArgs),
+    /// Print the total time spent on a task
+    Total,
+}
+
+#[derive(Debug, Args)]
+struct UnregisterArgs {
+    /// Also hide records of the task from record lists and reports
+    #[arg(long)]
+    hide_records: bool,
+}
+
+#[derive(Debug, Args)]
+struct LevelsArgs {
+    /// Task level
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=3))]
+    level: u8,
 }
 
 #[derive(Debug, Args)]
 struct StartArgs {
     /// Specify target date
     #[arg(short, long, value_name = "DATE")]
-    date: Option<String>,
+    date: Option<WorkingDate>,
+    /// Start the last logged task without selecting
+    #[arg(short, long)]
+    last: bool,
+    /// Begin at the current time without prompting
+    #[arg(short, long)]
+    now: bool,
+}
+
+#[derive(Debug, Args)]
+struct ArriveArgs {
+    /// Full name of the work task (e.g. office/desk) [default: $SGT_WORK_TASK or Work]
+    #[arg(short, long, value_name = "TASK")]
+    task: Option<String>,
 }
 
 #[derive(Debug, Args)]
 struct EndArgs {
     /// Specify target date
     #[arg(short, long, value_name = "DATE")]
-    date: Option<String>,
+    date: Option<WorkingDate>,
+    /// End the latest active record whatever its working date
+    #[arg(long, conflicts_with = "date")]
+    from_last: bool,
+    /// End at the current time without prompting
+    #[arg(short, long)]
+    now: bool,
+}
+
+#[derive(Debug, Args)]
+struct LogDurationArgs {
+    /// Duration of the record (e.g. 90m, 1h30m, 1:30)
+    duration: String,
+    /// Specify target date
+    #[arg(short, long, value_name = "DATE")]
+    date: Option<WorkingDate>,
 }
 
 #[derive(Debug, Args)]
 struct FixArgs {
     /// Specify target date
     #[arg(short, long, value_name = "DATE")]
-    date: Option<String>,
+    date: Option<WorkingDate>,
+    /// Shift all records of the date by an offset (e.g. +30m, -1h15m)
+    #[arg(long, value_name = "OFFSET", allow_hyphen_values = true)]
+    shift: Option<String>,
+    /// Ask for confirmation if the date is more than the days ago
+    #[arg(long, value_name = "DAYS", default_value_t = subcommand::fix::CONFIRM_DAYS)]
+    confirm_days: i64,
+}
+
+#[derive(Debug, Args)]
+struct EditArgs {
+    /// Record id (shown by `log --show-id`)
+    id: u32,
+}
+
+#[derive(Debug, Args)]
+struct SplitArgs {
+    /// Record id (shown by `log --show-id`)
+    id: u32,
+    /// Time to split the record at (e.g. 10:30)
+    #[arg(value_name = "HH:MM")]
+    time: String,
+}
+
+#[derive(Debug, Args)]
+struct MergeRecordsArgs {
+    /// Specify target date
+    #[arg(short, long, value_name = "DATE")]
+    date: Option<WorkingDate>,
+}
+
+#[derive(Debug, Args)]
+struct CopyDayArgs {
+    /// Date to copy records from
+    #[arg(long, value_name = "DATE")]
+    from: WorkingDate,
+    /// Date to copy records to
+    #[arg(long, value_name = "DATE")]
+    to: WorkingDate,
+    /// Copy even if the target date already has records
+    #[arg(short, long)]
+    force: bool,
 }
 
 #[derive(Debug, Args)]
@@ -82,9 +265,231 @@ struct LogArgs {
     all: bool,
     /// Print records with the specified date
     #[arg(short, long, value_name = "DATE", conflicts_with("month"))]
-    date: Option<String>,
-    /// Print records with the specified month
-    #[arg(short, long, value_name = "MONTH", conflicts_with("all"))]
+    date: Option<WorkingDate>,
+    /// Print records with the specified month (this month if omitted)
+    #[arg(short, long, value_name = "MONTH", num_args = 0..=1, conflicts_with("all"))]
+    month: Option<Option<String>>,
+    /// Print records with the specified ISO week (e.g. 2024-W12)
+    #[arg(
+        short = 'w',
+        long,
+        value_name = "WEEK",
+        conflicts_with_all(["all", "date", "month"])
+    )]
+    isoweek: Option<String>,
+    /// Print records from the specified date (through today unless --to is given)
+    #[arg(long, value_name = "DATE", conflicts_with_all(["all", "date", "month", "isoweek"]))]
+    from: Option<WorkingDate>,
+    /// Print records up to the specified date (from the earliest unless --from is given)
+    #[arg(long, value_name = "DATE", conflicts_with_all(["all", "date", "month", "isoweek"]))]
+    to: Option<WorkingDate>,
+    /// Print records in newest-first order
+    #[arg(short, long)]
+    reverse: bool,
+    /// Print record ids
+    #[arg(long)]
+    show_id: bool,
+    /// Number of task levels to show
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(1..=3))]
+    depth: Option<u8>,
+    /// Decimal places of percentages
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(0..=MAX_PERCENT_PRECISION as i64)
+    )]
+    percent_precision: u32,
+    /// Deduct break time within work records from the total duration
+    #[arg(long)]
+    deduct_breaks: bool,
+    /// Insert rows for unlogged time between records
+    #[arg(long)]
+    show_gaps: bool,
+    /// Print only records with the tag
+    #[arg(long, value_name = "TAG")]
+    tag: Option<String>,
+    /// Leave out records of unregistered tasks
+    #[arg(long)]
+    hide_inactive: bool,
+    /// Select a task and print only its records
+    #[arg(long)]
+    task: bool,
+    /// Clip records to a time window of each day (e.g. 09:00-12:00)
+    #[arg(long, value_name = "HH:MM-HH:MM")]
+    between: Option<String>,
+    /// Print dates with no records in the period instead of records
+    #[arg(long, conflicts_with_all(["all", "date"]))]
+    empty_days: bool,
+    /// Leave weekends (see --weekend) out of empty days
+    #[arg(long, requires("empty_days"))]
+    skip_weekends: bool,
+    /// Write to the file instead of stdout (`-` for stdout)
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+    /// Count records of a task separated by at most the gap (e.g. 30m, 1:00) as one session
+    #[arg(long, value_name = "DURATION")]
+    group_gap: Option<String>,
+    /// Draw bars of durations by task up to the width
+    #[arg(long, value_name = "N")]
+    bar_width: Option<usize>,
+    /// Print only the total worked time (HH:MM)
+    #[arg(long, conflicts_with_all(["show_gaps", "empty_days", "bar_width", "group_gap"]))]
+    sum_only: bool,
+    /// Format of records; summaries are printed only with tables
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        default_value = "table",
+        conflicts_with_all(["sum_only", "empty_days"])
+    )]
+    format: RecordFormat,
+    /// Print begin and end times as stored (YYYY-MM-DDTHH:MM:SS)
+    #[arg(long, conflicts_with_all(["sum_only", "empty_days"]))]
+    raw: bool,
+}
+
+#[derive(Debug, Args)]
+struct SummaryArgs {
+    /// Summarize records with the specified date
+    #[arg(short, long, value_name = "DATE", conflicts_with("month"))]
+    date: Option<WorkingDate>,
+    /// Summarize records with the specified month (this month if omitted)
+    #[arg(short, long, value_name = "MONTH", num_args = 0..=1)]
+    month: Option<Option<String>>,
+    /// Summarize records with the specified ISO week (e.g. 2024-W12)
+    #[arg(
+        short = 'w',
+        long,
+        value_name = "WEEK",
+        conflicts_with_all(["date", "month"])
+    )]
+    isoweek: Option<String>,
+    /// Summarize records from the first of this month through today
+    #[arg(long, conflicts_with_all(["date", "month", "isoweek"]))]
+    mtd: bool,
+    /// Summarize records from the first of this year through today
+    #[arg(long, conflicts_with_all(["date", "month", "isoweek", "mtd"]))]
+    ytd: bool,
+    /// Task level to group durations by
+    #[arg(short, long, value_name = "LEVEL", default_value = "level3")]
+    group_by: GroupBy,
+    /// Number of task levels to show (same as grouping by the level)
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u8).range(1..=3),
+        conflicts_with("group_by")
+    )]
+    depth: Option<u8>,
+    /// Decimal places of percentages
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(0..=MAX_PERCENT_PRECISION as i64)
+    )]
+    percent_precision: u32,
+    /// Count records of a task separated by at most the gap (e.g. 30m, 1:00) as one session
+    #[arg(long, value_name = "DURATION")]
+    group_gap: Option<String>,
+    /// Draw bars of durations by task up to the width
+    #[arg(long, value_name = "N")]
+    bar_width: Option<usize>,
+    /// Leave out records of unregistered tasks
+    #[arg(long)]
+    hide_inactive: bool,
+    /// Also split the total into billable and non-billable durations
+    #[arg(long)]
+    billable: bool,
+    /// Print total and average durations by day of the week (this month by default)
+    #[arg(long, conflicts_with_all([
+        "date", "mtd", "ytd", "group_by", "depth", "group_gap", "billable"
+    ]))]
+    by_weekday: bool,
+}
+
+#[derive(Debug, Args)]
+struct ExportArgs {
+    /// Export records with the specified date
+    #[arg(short, long, value_name = "DATE", conflicts_with("month"))]
+    date: Option<WorkingDate>,
+    /// Export records with the specified month
+    #[arg(short, long, value_name = "MONTH")]
+    month: Option<String>,
+    /// Export records with the specified ISO week (e.g. 2024-W12)
+    #[arg(
+        short = 'w',
+        long,
+        value_name = "WEEK",
+        conflicts_with_all(["date", "month"])
+    )]
+    isoweek: Option<String>,
+    /// Write to the file instead of stdout (`-` for stdout)
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+    /// Append to the output file, skipping the header if it has content
+    #[arg(long, requires("output"))]
+    append: bool,
+}
+
+#[derive(Debug, Args)]
+struct ImportArgs {
+    /// CSV file to import, or `-` for stdin
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum GroupBy {
+    Level1,
+    Level2,
+    Level3,
+}
+
+impl GroupBy {
+    /// Number of task levels to keep in a group name.
+    fn depth(self) -> Option<usize> {
+        match self {
+            GroupBy::Level1 => Some(1),
+            GroupBy::Level2 => Some(2),
+            GroupBy::Level3 => None,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct StatusArgs {
+    /// Daily target of worked time (e.g. 8:00)
+    #[arg(short, long, value_name = "HH:MM")]
+    target: Option<String>,
+    /// Print a single tab-separated line for scripts: `active TASK BEGIN MINUTES` or `idle`
+    #[arg(long, conflicts_with("target"))]
+    porcelain: bool,
+    /// Refresh the status in place every N seconds until interrupted
+    #[arg(
+        short,
+        long,
+        value_name = "N",
+        num_args = 0..=1,
+        default_missing_value = "5",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with("porcelain")
+    )]
+    watch: Option<u64>,
+}
+
+#[derive(Debug, Args)]
+struct AttendanceArgs {
+    /// Target month (this month if omitted)
+    #[arg(short, long, value_name = "MONTH")]
+    month: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct TimesheetArgs {
+    /// Target month (this month if omitted)
+    #[arg(short, long, value_name = "MONTH")]
     month: Option<String>,
 }
 
@@ -93,15 +498,128 @@ struct LsArgs {
     /// Print all tasks
     #[arg(short, long)]
     all: bool,
+    /// Order of tasks
+    #[arg(short, long, value_name = "ORDER", default_value = "name")]
+    sort: TaskSort,
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let db_path = setup_db("shigotolog", stderr())?;
+/// Output of records printed after changing them.
+fn mutation_output(quiet: bool) -> Box<dyn Write> {
+    if quiet {
+        Box::new(sink())
+    } else {
+        Box::new(stdout())
+    }
+}
+
+/// Month argument, defaulting to the current month when given without a value.
+fn or_this_month(month: Option<String>) -> String {
+    month.unwrap_or_else(|| WorkingDate::today().format("%Y-%m"))
+}
+
+/// Days of the week not expected to be worked, from `--weekend` or `SGT_WEEKEND`.
+fn resolve_weekend(weekend: Option<String>) -> Result<Vec<Weekday>, Box<dyn std::error::Error>> {
+    match weekend.or_else(|| std::env::var("SGT_WEEKEND").ok()) {
+        Some(days) => Ok(parse_weekdays(&days)?),
+        None => Ok(DEFAULT_WEEKEND.to_vec()),
+    }
+}
 
+/// Longest gap between records of a task in one session, from `--group-gap`.
+fn parse_group_gap(gap: Option<String>) -> Result<Option<TimeDelta>, CliError> {
+    gap.map(|gap| parse_duration(&gap).map_err(|e| CliError::invalid("group gap", &gap, e)))
+        .transpose()
+}
+
+fn main() {
     let args = Cli::parse();
+    let error_format = args.error_format;
+
+    if let Err(e) = run(args) {
+        match error_format {
+            ErrorFormat::Text => eprintln!("Error: {:?}", e),
+            ErrorFormat::Json => eprintln!("{}", error::to_json(e.as_ref())),
+        }
+        std::process::exit(ErrorKind::of(e.as_ref()).exit_code());
+    }
+}
+
+fn run(args: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    if let Ok(offset) = std::env::var("SGT_UTC_OFFSET") {
+        set_fixed_offset(parse_utc_offset(&offset)?)?;
+    }
+
+    // setting up the database may create or migrate it
+    match args.command {
+        Commands::Doctor => {
+            if !subcommand::doctor::run(&db_path("shigotolog")?, stdout())? {
+                std::process::exit(ISSUES_FOUND_EXIT_CODE);
+            }
+            return Ok(());
+        }
+        Commands::Path => {
+            writeln!(stdout(), "{}", db_path("shigotolog")?.display())?;
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let db_path = setup_db("shigotolog", stderr())?;
+    // tables may be missing if the database was emptied or created by another tool
+    if !matches!(args.command, Commands::Init(_))
+        && !SQLiteDatabase::open_r(&db_path)?.is_ready()?
+    {
+        return Err(CliError::new(
+            ErrorKind::NotInitialized,
+            "Database not initialized; run `sgt init`",
+        )
+        .into());
+    }
+    let quiet = args.quiet;
+    let output_to_file = match &args.command {
+        Commands::Log(LogArgs { output, .. }) => output.as_deref().is_some_and(|p| !is_stdout(p)),
+        _ => false,
+    };
+    let output_is_terminal = stdout().is_terminal() && !output_to_file;
+    set_table_style(args.style);
+    let language = match args.lang {
+        Some(language) => language,
+        None => match std::env::var("SGT_LANG") {
+            Ok(lang) => Language::from_str(&lang, true)
+                .map_err(|e| CliError::invalid("SGT_LANG", &lang, e))?,
+            Err(_) => Language::default(),
+        },
+    };
+    set_language(language);
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    set_color(!args.no_color && !no_color_env && !output_to_file);
+    // the terminal width is only known when the shell exports COLUMNS
+    let terminal_width = match output_is_terminal {
+        true => std::env::var("COLUMNS").ok().and_then(|v| v.parse().ok()),
+        false => None,
+    };
+    set_max_width(match args.wide {
+        true => None,
+        false => args.max_width.or(terminal_width),
+    });
+    // hyperlinks are escape sequences that would end up in files as is
+    if let Ok(template) = std::env::var("SGT_TICKET_URL_TEMPLATE") {
+        if output_is_terminal {
+            set_ticket_url_template(template);
+        }
+    }
+    set_display_format(args.date_format.as_deref(), args.time_format.as_deref())?;
+    // parsed only by commands that count working days
+    let weekend = args.weekend;
+
     match args.command {
-        Commands::Init => {
-            if let Ok(true) = prompt::confirm_init() {
+        Commands::Init(args) => {
+            if !args.yes && !stdin().is_terminal() {
+                return Err(
+                    "Confirmation needs a terminal. Use --yes to initialize anyway.".into(),
+                );
+            }
+            if args.yes || prompt::confirm_init().unwrap_or(false) {
                 let db = SQLiteDatabase::open_rwc(&db_path)?;
                 subcommand::init::run(&db, std::io::stderr())?;
             }
@@ -111,33 +629,224 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             match task_cmd {
                 TaskCommands::Register => {
                     let db = SQLiteDatabase::open_rw(&db_path)?;
-                    subcommand::task::register::run(&db)?;
+                    subcommand::task::register::run(&db, stdout())?;
                 }
-                TaskCommands::Unregister => {
+                TaskCommands::Unregister(args) => {
                     let db = SQLiteDatabase::open_rw(&db_path)?;
-                    subcommand::task::unregister::run(&db)?;
+                    subcommand::task::unregister::run(&db, args.hide_records, stdout())?;
+                }
+                TaskCommands::Reactivate => {
+                    let db = SQLiteDatabase::open_rw(&db_path)?;
+                    subcommand::task::reactivate::run(&db, stdout())?;
                 }
                 TaskCommands::Ls(args) => {
                     let db = SQLiteDatabase::open_r(&db_path)?;
-                    subcommand::task::ls::run(&db, args.all, stdout())?;
+                    subcommand::task::ls::run(&db, args.all, args.sort, stdout())?;
+                }
+                TaskCommands::Total => {
+                    let db = SQLiteDatabase::open_r(&db_path)?;
+                    subcommand::task::total::run(&db, stdout())?;
+                }
+                TaskCommands::Levels(args) => {
+                    let db = SQLiteDatabase::open_r(&db_path)?;
+                    subcommand::task::levels::run(&db, args.level.into(), stdout())?;
                 }
             }
         }
         Commands::Start(args) => {
             let db = SQLiteDatabase::open_rw(&db_path)?;
-            subcommand::start::run(&db, args.date, stdout())?;
+            subcommand::start::run(
+                &db,
+                args.date,
+                None,
+                args.last,
+                args.now,
+                mutation_output(quiet),
+            )?;
         }
         Commands::End(args) => {
             let db = SQLiteDatabase::open_rw(&db_path)?;
-            subcommand::end::run(&db, args.date, stdout())?;
+            subcommand::end::run(
+                &db,
+                args.date,
+                args.from_last,
+                args.now,
+                mutation_output(quiet),
+            )?;
+        }
+        Commands::Arrive(args) => {
+            let db = SQLiteDatabase::open_rw(&db_path)?;
+            let task = args
+                .task
+                .or_else(|| std::env::var("SGT_WORK_TASK").ok())
+                .unwrap_or_else(|| subcommand::arrive::DEFAULT_WORK_TASK.into());
+            subcommand::arrive::run(&db, &task, mutation_output(quiet))?;
+        }
+        Commands::Leave => {
+            let db = SQLiteDatabase::open_rw(&db_path)?;
+            // records left open on past days need their own end time
+            warn_open_record(&db, &WorkingDate::today(), stderr())?;
+            subcommand::end::run(&db, None, false, true, mutation_output(quiet))?;
+        }
+        Commands::Pause => {
+            let db = SQLiteDatabase::open_rw(&db_path)?;
+            subcommand::pause::run(&db, mutation_output(quiet))?;
+        }
+        Commands::Resume => {
+            let db = SQLiteDatabase::open_rw(&db_path)?;
+            subcommand::resume::run(&db, mutation_output(quiet))?;
+        }
+        Commands::LogDuration(args) => {
+            let db = SQLiteDatabase::open_rw(&db_path)?;
+            subcommand::log_duration::run(&db, args.date, &args.duration, mutation_output(quiet))?;
         }
         Commands::Fix(args) => {
             let db = SQLiteDatabase::open_rw(&db_path)?;
-            subcommand::fix::run(&db, args.date, stdout())?;
+            match args.shift {
+                Some(shift) => subcommand::fix::shift(
+                    &db,
+                    args.date,
+                    &shift,
+                    args.confirm_days,
+                    mutation_output(quiet),
+                )?,
+                None => {
+                    subcommand::fix::run(&db, args.date, args.confirm_days, mutation_output(quiet))?
+                }
+            }
+        }
+        Commands::Edit(args) => {
+            let db = SQLiteDatabase::open_rw(&db_path)?;
+            subcommand::edit::run(&db, args.id, mutation_output(quiet))?;
+        }
+        Commands::Split(args) => {
+            let db = SQLiteDatabase::open_rw(&db_path)?;
+            subcommand::split::run(&db, args.id, &args.time, mutation_output(quiet))?;
+        }
+        Commands::MergeRecords(args) => {
+            let db = SQLiteDatabase::open_rw(&db_path)?;
+            subcommand::merge_records::run(&db, args.date, mutation_output(quiet))?;
+        }
+        Commands::CopyDay(args) => {
+            let db = SQLiteDatabase::open_rw(&db_path)?;
+            subcommand::copy_day::run(
+                &db,
+                &args.from,
+                &args.to,
+                args.force,
+                mutation_output(quiet),
+            )?;
         }
         Commands::Log(args) => {
             let db = SQLiteDatabase::open_r(&db_path)?;
-            subcommand::log::run(&db, args.date, args.month, args.all, stdout())?;
+            let options = LogOptions {
+                date: args.date,
+                month: args.month.map(or_this_month),
+                isoweek: args.isoweek,
+                from: args.from,
+                to: args.to,
+                show_all: args.all,
+                reverse: args.reverse,
+                show_id: args.show_id,
+                depth: args.depth.map(usize::from),
+                percent_precision: args.percent_precision,
+                deduct_breaks: args.deduct_breaks,
+                show_gaps: args.show_gaps,
+                tag: args.tag,
+                hide_inactive: args.hide_inactive,
+                select_task: args.task,
+                empty_days: args.empty_days,
+                skip_weekends: args.skip_weekends,
+                weekend: match args.skip_weekends {
+                    true => resolve_weekend(weekend)?,
+                    false => vec![],
+                },
+                between: args.between,
+                group_gap: parse_group_gap(args.group_gap)?,
+                bar_width: args.bar_width,
+                sum_only: args.sum_only,
+                format: args.format,
+                raw: args.raw,
+            };
+            match args.output.filter(|path| !is_stdout(path)) {
+                Some(path) => {
+                    let mut file = AtomicFile::create(&path, false)?;
+                    subcommand::log::run(&db, options, &mut file)?;
+                    file.commit()?;
+                }
+                None => subcommand::log::run(&db, options, stdout())?,
+            }
+        }
+        Commands::Summary(args) if args.by_weekday => {
+            let db = SQLiteDatabase::open_r(&db_path)?;
+            subcommand::summary::by_weekday(
+                &db,
+                args.month.map(or_this_month),
+                args.isoweek,
+                stdout(),
+            )?;
+        }
+        Commands::Summary(args) => {
+            let db = SQLiteDatabase::open_r(&db_path)?;
+            let options = SummaryOptions {
+                date: args.date,
+                month: args.month.map(or_this_month),
+                isoweek: args.isoweek,
+                mtd: args.mtd,
+                ytd: args.ytd,
+                depth: args
+                    .depth
+                    .map(usize::from)
+                    .or_else(|| args.group_by.depth()),
+                percent_precision: args.percent_precision,
+                group_gap: parse_group_gap(args.group_gap)?,
+                bar_width: args.bar_width,
+                hide_inactive: args.hide_inactive,
+                billable: args.billable,
+            };
+            subcommand::summary::run(&db, options, stdout())?;
+        }
+        Commands::Export(args) => {
+            let db = SQLiteDatabase::open_r(&db_path)?;
+            subcommand::export::run(
+                &db,
+                args.date,
+                args.month,
+                args.isoweek,
+                args.output,
+                args.append,
+                stdout(),
+            )?;
+        }
+        Commands::Import(args) => {
+            let db = SQLiteDatabase::open_rw(&db_path)?;
+            subcommand::import::run(&db, &args.file, stdout())?;
+        }
+        Commands::Status(args) => {
+            let db = SQLiteDatabase::open_r(&db_path)?;
+            subcommand::status::run(&db, args.target, args.porcelain, args.watch, stdout())?;
+        }
+        Commands::Attendance(args) => {
+            let db = SQLiteDatabase::open_r(&db_path)?;
+            let weekend = resolve_weekend(weekend)?;
+            subcommand::attendance::run(&db, &or_this_month(args.month), &weekend, stdout())?;
+        }
+        Commands::Timesheet(args) => {
+            let db = SQLiteDatabase::open_r(&db_path)?;
+            subcommand::timesheet::run(&db, &or_this_month(args.month), stdout())?;
+        }
+        Commands::Verify => {
+            let db = SQLiteDatabase::open_r(&db_path)?;
+            if !subcommand::verify::run(&db, stdout())? {
+                std::process::exit(ISSUES_FOUND_EXIT_CODE);
+            }
+        }
+        Commands::Doctor | Commands::Path => {
+            unreachable!("handled before setting up the database")
+        }
+        Commands::Normalize => {
+            let db = SQLiteDatabase::open_rw(&db_path)?;
+            subcommand::normalize::run(&db, stdout())?;
         }
     }
 
@@ -0,0 +1,72 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// File written through a temporary file in the same directory.
+///
+/// The file at the path is replaced only by `commit`, so an interrupted write leaves it intact.
+/// The temporary file is removed if dropped without committing.
+pub struct AtomicFile {
+    path: PathBuf,
+    temp_path: PathBuf,
+    file: Option<File>,
+}
+
+impl AtomicFile {
+    /// Creates a temporary file for `path`, starting with the content of `path` if `append` is set.
+    pub fn create(path: &Path, append: bool) -> Result<Self, Box<dyn Error>> {
+        let file_name = path.file_name().ok_or("invalid output file")?;
+        let mut temp_name = file_name.to_os_string();
+        temp_name.push(format!(".{}.tmp", std::process::id()));
+        let temp_path = path.with_file_name(temp_name);
+
+        if append && path.exists() {
+            fs::copy(path, &temp_path)?;
+        }
+        let file = File::options().create(true).append(true).open(&temp_path)?;
+
+        Ok(Self {
+            path: path.to_owned(),
+            temp_path,
+            file: Some(file),
+        })
+    }
+
+    /// Whether the file has content, including the content kept for appending.
+    pub fn has_content(&self) -> Result<bool, Box<dyn Error>> {
+        Ok(fs::metadata(&self.temp_path)?.len() > 0)
+    }
+
+    /// Replaces the file at the path with the written content.
+    pub fn commit(mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(file) = self.file.take() {
+            file.sync_all()?;
+        }
+        fs::rename(&self.temp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.as_mut().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.as_mut().unwrap().flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if self.file.is_some() {
+            let _ = fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+/// Whether the output path means stdout.
+pub fn is_stdout(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
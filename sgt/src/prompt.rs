@@ -11,6 +11,11 @@ pub fn text_input_with_default(message: &str, default: &str) -> Result<String, I
     Text::new(message).with_default(default).prompt()
 }
 
+/// Text input prompt with an editable initial value, which can be cleared unlike a default.
+pub fn text_input_with_initial_value(message: &str, initial: &str) -> Result<String, InquireError> {
+    Text::new(message).with_initial_value(initial).prompt()
+}
+
 /// Confirm prompt.
 pub fn confirm(message: &str, default: bool) -> Result<bool, InquireError> {
     Confirm::new(message).with_default(default).prompt()
@@ -40,9 +45,31 @@ pub fn confirm_taskname_input(
         .prompt()
 }
 
+/// Number of candidates shown at once in select prompts.
+const SELECT_PAGE_SIZE: usize = 15;
+
 /// Select prompt.
 pub fn select(candidates: Vec<String>, message: &str) -> Result<String, InquireError> {
-    Select::new(message, candidates).prompt()
+    build_select(candidates, message).prompt()
+}
+
+/// Select prompt with the cursor placed on the specified candidate.
+pub fn select_with_cursor(
+    candidates: Vec<String>,
+    message: &str,
+    cursor: usize,
+) -> Result<String, InquireError> {
+    build_select(candidates, message)
+        .with_starting_cursor(cursor)
+        .prompt()
+}
+
+/// Select prompt filtered by fuzzy matching of typed input.
+fn build_select(candidates: Vec<String>, message: &str) -> Select<'_, String> {
+    Select::new(message, candidates)
+        .with_page_size(SELECT_PAGE_SIZE)
+        .with_scorer(&Select::DEFAULT_SCORER)
+        .with_help_message("↑↓ to move, type to filter, enter to select")
 }
 
 /// Warning color config.
@@ -1,6 +1,23 @@
+pub mod arrive;
+pub mod attendance;
+pub mod copy_day;
+pub mod doctor;
+pub mod edit;
 pub mod end;
+pub mod export;
 pub mod fix;
+pub mod import;
 pub mod init;
 pub mod log;
+pub mod log_duration;
+pub mod merge_records;
+pub mod normalize;
+pub mod pause;
+pub mod resume;
+pub mod split;
 pub mod start;
+pub mod status;
+pub mod summary;
 pub mod task;
+pub mod timesheet;
+pub mod verify;
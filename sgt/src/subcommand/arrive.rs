@@ -0,0 +1,73 @@
+use std::error::Error;
+use std::io::Write;
+
+use shigotolog::datetime::WorkingDate;
+use shigotolog::repository::Manipulation;
+use shigotolog::sqlite_db::SQLiteDatabase;
+
+use crate::subcommand::start;
+
+/// Task logged on arrival unless specified otherwise.
+pub const DEFAULT_WORK_TASK: &str = "Work";
+
+/// Starts the work task now as the first record of today, capturing the arrival time.
+///
+/// The record lasts until the next task is started, as `start` ends the active record.
+/// `task_name` is the full name of a registered task, such as `Work` or `office/desk`.
+pub fn run(db: &SQLiteDatabase, task_name: &str, writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let date = WorkingDate::today();
+    if !db.get_records_by_date(&date)?.is_empty() {
+        return Err("Today already has records; use `sgt start` instead".into());
+    }
+
+    let task = db
+        .tasks()?
+        .into_iter()
+        .find(|task| task.is_active && task.format_name("/") == task_name)
+        .ok_or_else(|| format!("Task {} is not registered", task_name))?;
+
+    start::run(db, Some(date), Some(task), false, true, writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use shigotolog::datetime::TaskTime;
+    use shigotolog::task::{Task, TaskRecord};
+
+    fn prep_db() -> Result<SQLiteDatabase, Box<dyn Error>> {
+        let db = SQLiteDatabase::open_rwc(":memory:")?;
+        db.initialize()?;
+        let task = Task::new(None, Some(DEFAULT_WORK_TASK), None, None, "", false, true);
+        db.register_task(&task)?;
+        Ok(db)
+    }
+
+    #[test]
+    fn test_arrive_today_has_records() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        let task = db.tasks()?.pop().unwrap();
+        let record = TaskRecord::new(None, task, WorkingDate::today(), TaskTime::now(), None);
+        db.add_record(&record)?;
+
+        let mut output = vec![];
+        let error = run(&db, DEFAULT_WORK_TASK, &mut output).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Today already has records; use `sgt start` instead"
+        );
+        assert!(output.is_empty());
+        assert_eq!(db.get_records_by_date(&WorkingDate::today())?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_arrive_task_not_registered() -> Result<(), Box<dyn Error>> {
+        let db = prep_db()?;
+        let error = run(&db, "office/desk", vec![]).unwrap_err();
+        assert_eq!(error.to_string(), "Task office/desk is not registered");
+        assert!(db.get_records_by_date(&WorkingDate::today())?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+use std::error::Error;
+use std::io::Write;
+
+use chrono::Weekday;
+
+use shigotolog::datetime::{is_working_day, WorkingDate};
+use shigotolog::repository::Manipulation;
+use shigotolog::sqlite_db::SQLiteDatabase;
+
+use crate::table;
+
+/// Prints attendance of the month, with the number of working days through today
+/// counted on weekdays other than `weekend`.
+pub fn run(
+    db: &SQLiteDatabase,
+    month: &str,
+    weekend: &[Weekday],
+    mut writer: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let (st, en) = WorkingDate::parse_ym(month)?;
+    let records = db.get_records_in_period(&st, &en)?;
+
+    let working_days = st
+        .iter_days(&en.min(WorkingDate::today()))
+        .filter(|date| is_working_day(date, weekend, &[]))
+        .count();
+
+    writeln!(writer, "{}", table::attendance(&records))?;
+    writeln!(writer, "Working days: {}", working_days)?;
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use std::error::Error;
+use std::io::Write;
+
+use chrono::TimeDelta;
+use shigotolog::datetime::WorkingDate;
+use shigotolog::repository::Manipulation;
+use shigotolog::sqlite_db::SQLiteDatabase;
+use shigotolog::task::TaskRecord;
+
+use crate::table;
+
+/// Copies records of the date `from` to the date `to` with the same times of day.
+///
+/// Records without end time are not copied. Fails without changes if `to` already has records
+/// unless `force` is set, and even then records already on `to` are skipped as duplicates.
+pub fn run(
+    db: &SQLiteDatabase,
+    from: &WorkingDate,
+    to: &WorkingDate,
+    force: bool,
+    mut writer: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    if from == to {
+        return Err("source and target dates are the same".into());
+    }
+    let existing = db.get_records_by_date(to)?;
+    if !force && !existing.is_empty() {
+        return Err(format!("{} already has records. Use --force to copy anyway.", to).into());
+    }
+
+    let delta = TimeDelta::days(to.days_since(from));
+    let (duplicates, records): (Vec<_>, Vec<_>) = db
+        .get_records_by_date(from)?
+        .into_iter()
+        .filter(|record| record.end.is_some())
+        .map(|record| TaskRecord {
+            id: None,
+            working_date: to.clone(),
+            begin: record.begin.shift(delta),
+            end: record.end.map(|end| end.shift(delta)),
+            session_id: None,
+            ..record
+        })
+        .partition(|record| existing.iter().any(|other| record.is_duplicate_of(other)));
+
+    db.transaction(|db| {
+        for record in &records {
+            db.add_record(record)?;
+        }
+        Ok(())
+    })?;
+
+    writeln!(writer, "Copied {} records.", records.len())?;
+    if !duplicates.is_empty() {
+        writeln!(
+            writer,
+            "Skipped {} records already on {}.",
+            duplicates.len(),
+            to
+        )?;
+    }
+    if !records.is_empty() {
+        // show records
+        let records = db.get_records_by_date(to)?;
+        writeln!(writer, "{}", table::record_list(&records))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use shigotolog::datetime::TaskTime;
+    use shigotolog::task::Task;
+
+    #[test]
+    fn test_copy_day_force_twice() -> Result<(), Box<dyn Error>> {
+        let db = SQLiteDatabase::open_rwc(":memory:")?;
+        db.initialize()?;
+        db.register_task(&Task::new(None, Some("a"), None, None, "", false, true))?;
+        let task = db.tasks()?.pop().unwrap();
+        let from = WorkingDate::parse("2021-01-04")?;
+        let to = WorkingDate::parse("2021-01-05")?;
+        for (begin, end) in [("09:00", "12:00"), ("13:00", "17:00")] {
+            let record = TaskRecord::new(
+                None,
+                task.clone(),
+                from.clone(),
+                TaskTime::parse_with_date(&from, begin)?,
+                Some(TaskTime::parse_with_date(&from, end)?),
+            );
+            db.add_record(&record)?;
+        }
+
+        run(&db, &from, &to, false, vec![])?;
+        assert!(run(&db, &from, &to, false, vec![]).is_err());
+
+        // copying again only skips the records already there
+        let mut output = vec![];
+        run(&db, &from, &to, true, &mut output)?;
+        let output = String::from_utf8(output)?;
+        assert!(output.starts_with("Copied 0 records.\nSkipped 2 records already on 2021-01-05.\n"));
+        assert_eq!(db.get_records_by_date(&to)?.len(), 2);
+        Ok(())
+    }
+}
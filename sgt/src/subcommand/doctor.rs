@@ -0,0 +1,102 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+use shigotolog::datetime::WorkingDate;
+use shigotolog::repository::Manipulation;
+use shigotolog::sqlite_db::{SQLiteDatabase, SCHEMA_VERSION};
+use shigotolog::verify::find_issues;
+
+/// Prints diagnostics of the database at `db_path` without changing anything.
+/// Returns `false` if any problem is found.
+pub fn run(db_path: &Path, mut writer: impl Write) -> Result<bool, Box<dyn Error>> {
+    writeln!(writer, "Database: {}", db_path.to_string_lossy())?;
+
+    let mut healthy = true;
+    let mut report = |writer: &mut dyn Write, ok: bool, item: &str, detail: &str| {
+        healthy &= ok;
+        let mark = if ok { "ok" } else { "NG" };
+        writeln!(writer, "[{}] {}: {}", mark, item, detail)
+    };
+
+    if !db_path.exists() {
+        report(&mut writer, false, "exists", "no (run `sgt init`)")?;
+        return Ok(false);
+    }
+    report(&mut writer, true, "exists", "yes")?;
+
+    // opening without truncating or creating leaves the file as is
+    match OpenOptions::new().write(true).open(db_path) {
+        Ok(_) => report(&mut writer, true, "writable", "yes")?,
+        Err(e) => report(&mut writer, false, "writable", &format!("no ({})", e))?,
+    }
+
+    let db = match SQLiteDatabase::open_r(db_path) {
+        Ok(db) => db,
+        Err(e) => {
+            report(&mut writer, false, "readable", &e.to_string())?;
+            return Ok(false);
+        }
+    };
+
+    match db.integrity_check() {
+        Ok(problems) if problems.is_empty() => report(&mut writer, true, "integrity", "ok")?,
+        Ok(problems) => report(&mut writer, false, "integrity", &problems.join("; "))?,
+        Err(e) => {
+            report(&mut writer, false, "integrity", &e.to_string())?;
+            return Ok(false);
+        }
+    }
+
+    if !db.is_ready()? {
+        report(&mut writer, false, "tables", "missing (run `sgt init`)")?;
+        return Ok(false);
+    }
+    report(&mut writer, true, "tables", "ok")?;
+
+    let version = db.schema_version()?;
+    match version.cmp(&SCHEMA_VERSION) {
+        Ordering::Equal => report(&mut writer, true, "schema", &format!("version {}", version))?,
+        Ordering::Less => report(
+            &mut writer,
+            true,
+            "schema",
+            &format!(
+                "version {} (migrated to {} by the next command)",
+                version, SCHEMA_VERSION
+            ),
+        )?,
+        Ordering::Greater => report(
+            &mut writer,
+            false,
+            "schema",
+            &format!(
+                "version {} is newer than supported version {}",
+                version, SCHEMA_VERSION
+            ),
+        )?,
+    }
+
+    report(&mut writer, true, "tasks", &db.task_count()?.to_string())?;
+    report(
+        &mut writer,
+        true,
+        "records",
+        &db.record_count()?.to_string(),
+    )?;
+
+    // records cannot be read until the schema is migrated
+    if version == SCHEMA_VERSION {
+        let issues = find_issues(&db.records()?, &WorkingDate::today());
+        if issues.is_empty() {
+            report(&mut writer, true, "issues", "none")?;
+        } else {
+            let detail = format!("{} found (run `sgt verify`)", issues.len());
+            report(&mut writer, false, "issues", &detail)?;
+        }
+    }
+
+    Ok(healthy)
+}
@@ -0,0 +1,67 @@
+use std::error::Error;
+use std::io::Write;
+
+use shigotolog::datetime::TaskTime;
+use shigotolog::repository::Manipulation;
+use shigotolog::sqlite_db::SQLiteDatabase;
+use shigotolog::task::parse_tags;
+
+use crate::prompt;
+use crate::table;
+use crate::util::{format_time_on, map_tasks_with_current};
+
+pub fn run(db: &SQLiteDatabase, id: u32, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let mut record = db
+        .get_record(id)
+        .map_err(|_| format!("No record with id {}", id))?;
+    let date = record.working_date.clone();
+
+    writeln!(
+        writer,
+        "{}",
+        table::record_list_with_options(&[record.clone()], true, None, false, false)
+    )?;
+
+    let tasks = db.tasks()?;
+    let (task_map, keys, cursor) = map_tasks_with_current(tasks, &record.task);
+    if let Ok(key) = prompt::select_with_cursor(keys, "Task:", cursor) {
+        record.task = task_map.get(&key).unwrap().clone();
+    }
+
+    if let Ok(begin_time) =
+        prompt::text_input_with_default("Begin time", &format_time_on(&date, &record.begin))
+    {
+        record.begin = TaskTime::parse_with_date_or_datetime(&date, &begin_time)?;
+    };
+
+    // records may end on a later date, which is then given with the time
+    let end = match &record.end {
+        Some(time) => format_time_on(&date, time),
+        None => "".to_string(),
+    };
+    if let Ok(end_time) = prompt::text_input_with_default("End time", &end) {
+        // leaving the end time empty keeps the record running
+        record.end = if end_time.is_empty() {
+            None
+        } else {
+            Some(TaskTime::parse_with_date_or_datetime(&date, &end_time)?)
+        };
+    };
+
+    let note = record.note.clone().unwrap_or_default();
+    if let Ok(note) = prompt::text_input_with_default("Note", &note) {
+        record.note = Some(note).filter(|note| !note.is_empty());
+    };
+    if let Ok(tags) = prompt::text_input_with_default("Tags", &record.tags.join(", ")) {
+        record.tags = parse_tags(&tags);
+    };
+    db.add_record(&record)?;
+    // show records
+    let records = db.get_records_by_date(&date)?;
+    writeln!(
+        writer,
+        "{}",
+        table::record_list_with_options(&records, true, None, false, false)
+    )?;
+    Ok(())
+}
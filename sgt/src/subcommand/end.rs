@@ -1,5 +1,7 @@
 use std::error::Error;
-use std::io::Write;
+use std::io::{stderr, Write};
+
+use chrono::TimeDelta;
 
 use shigotolog::datetime::{TaskTime, TimeDisplay, WorkingDate};
 use shigotolog::repository::{Manipulation, State};
@@ -7,28 +9,51 @@ use shigotolog::sqlite_db::SQLiteDatabase;
 
 use crate::prompt;
 use crate::table;
+use crate::util::format_time_on;
 
+/// Ends the active record of the date, or with `from_last`, the latest open record of any date.
 pub fn run(
     db: &SQLiteDatabase,
-    date: Option<String>,
+    date: Option<WorkingDate>,
+    from_last: bool,
+    use_now: bool,
     mut writer: impl Write,
 ) -> Result<(), Box<dyn Error>> {
-    let date = if let Some(date) = date {
-        WorkingDate::parse(&date)?
+    let (date, active_record) = if from_last {
+        let record = db.latest_open_record()?;
+        let date = record
+            .as_ref()
+            .map_or_else(WorkingDate::today, |record| record.working_date.clone());
+        (date, record)
     } else {
-        WorkingDate::today()
+        let date = date.unwrap_or_else(WorkingDate::today);
+        match db.current_state(&date)? {
+            State::Active(record) => (date, Some(record)),
+            _ => (date, None),
+        }
     };
 
-    let current_time = TaskTime::now();
-    let state = db.current_state(&date)?;
+    // the current time is given with its date if it is past the working date
+    let current_time = format_time_on(&date, &TaskTime::now());
 
-    if let State::Active(mut last_record) = state {
-        if let Ok(end_hm) =
-            prompt::text_input_with_default("End time", &current_time.to_string_hm())
-        {
-            let end = TaskTime::parse_with_date(&date, &end_hm)?;
-            if last_record.begin > end {
-                panic!("end time is earlier than start time")
+    if let Some(mut last_record) = active_record {
+        let end_hm = if use_now {
+            Ok(current_time)
+        } else {
+            prompt::text_input_with_default("End time", &current_time)
+        };
+        if let Ok(end_hm) = end_hm {
+            let end = TaskTime::parse_with_date_or_datetime(&date, &end_hm)?;
+            let duration = &end - &last_record.begin;
+            if duration < TimeDelta::zero() {
+                return Err("end time is earlier than start time".into());
+            }
+            if duration > TimeDelta::hours(24) {
+                writeln!(
+                    stderr(),
+                    "Warning: the record spans {} (over 24 hours)",
+                    duration.to_string_hm()
+                )?;
             }
             last_record.end = Some(end);
             db.add_record(&last_record)?;
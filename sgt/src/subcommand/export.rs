@@ -0,0 +1,67 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::io::{stderr, Write};
+use std::path::PathBuf;
+
+use shigotolog::datetime::WorkingDate;
+use shigotolog::repository::Manipulation;
+use shigotolog::sqlite_db::SQLiteDatabase;
+
+use crate::format::{CsvFormatter, RecordFormatter};
+use crate::output::{is_stdout, AtomicFile};
+
+/// Exports records as CSV to `output`, or to `writer` if not specified or `-`.
+///
+/// The output file is replaced only after all records are written.
+pub fn run(
+    db: &SQLiteDatabase,
+    date: Option<WorkingDate>,
+    month: Option<String>,
+    isoweek: Option<String>,
+    output: Option<PathBuf>,
+    append: bool,
+    mut writer: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let records = if let Some(date) = &date {
+        db.get_records_by_date(date)?
+    } else if let Some(arg_yearmonth) = &month {
+        let (st, en) = WorkingDate::parse_ym(arg_yearmonth)?;
+        db.get_records_in_period(&st, &en)?
+    } else if let Some(arg_isoweek) = &isoweek {
+        let (st, en) = WorkingDate::parse_isoweek(arg_isoweek)?;
+        db.get_records_in_period(&st, &en)?
+    } else {
+        db.records()?
+    };
+
+    let tasks = records
+        .iter()
+        .map(|record| (record.task.format_name("/"), &record.task.description))
+        .collect::<HashSet<_>>();
+    let summary = format!(
+        "Exported {} records across {} tasks.",
+        records.len(),
+        tasks.len()
+    );
+
+    match output.filter(|path| !is_stdout(path)) {
+        Some(path) => {
+            let mut file = AtomicFile::create(&path, append)?;
+            // the header is written only once when appending to an existing file
+            let has_content = file.has_content()?;
+            let formatter = CsvFormatter {
+                header: !has_content,
+            };
+            formatter.write_records(&records, &mut file)?;
+            file.commit()?;
+            writeln!(writer, "{}", summary)?;
+        }
+        None => {
+            CsvFormatter { header: true }.write_records(&records, &mut writer)?;
+            writer.flush()?;
+            // keep the CSV on stdout clean
+            writeln!(stderr(), "{}", summary)?;
+        }
+    }
+    Ok(())
+}
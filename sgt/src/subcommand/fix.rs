@@ -1,43 +1,86 @@
 use std::error::Error;
-use std::io::Write;
+use std::io::{stdin, IsTerminal, Write};
 
-use shigotolog::datetime::{TaskTime, TimeDisplay, WorkingDate};
+use shigotolog::datetime::{parse_shift, TaskTime, WorkingDate};
 use shigotolog::repository::Manipulation;
 use shigotolog::sqlite_db::SQLiteDatabase;
+use shigotolog::task::parse_tags;
 
+use crate::error::CliError;
 use crate::prompt;
 use crate::table;
-use crate::util::map_records;
+use crate::util::{format_time_on, map_records};
+
+/// Default number of days after which fixing a date needs confirmation.
+pub const CONFIRM_DAYS: i64 = 7;
+
+/// Asks whether to go on fixing `date` if it is more than `confirm_days` days ago.
+///
+/// Fails without a terminal to ask on, rather than doing nothing as if declined.
+fn confirm_date(date: &WorkingDate, confirm_days: i64) -> Result<bool, Box<dyn Error>> {
+    let days = WorkingDate::today().days_since(date);
+    if days <= confirm_days {
+        return Ok(true);
+    }
+    if !stdin().is_terminal() {
+        return Err(format!(
+            "Fixing {} ({} days ago) needs confirmation on a terminal. \
+            Pass --confirm-days {} to fix it anyway.",
+            date.to_string_display(),
+            days,
+            days
+        )
+        .into());
+    }
+    let message = format!(
+        "{} is {} days ago. Fix records of the date?",
+        date.to_string_display(),
+        days
+    );
+    Ok(prompt::confirm(&message, false).unwrap_or(false))
+}
 
 pub fn run(
     db: &SQLiteDatabase,
-    date: Option<String>,
+    date: Option<WorkingDate>,
+    confirm_days: i64,
     mut writer: impl Write,
 ) -> Result<(), Box<dyn Error>> {
-    let date = if let Some(date) = date {
-        WorkingDate::parse(&date)?
-    } else {
-        WorkingDate::today()
-    };
+    let date = date.unwrap_or_else(WorkingDate::today);
+    if !confirm_date(&date, confirm_days)? {
+        return Ok(());
+    }
 
     let records = db.get_records_by_date(&date)?;
+    if records.is_empty() {
+        writeln!(writer, "No Records")?;
+        return Ok(());
+    }
     let (mut record_map, record_s) = map_records(records);
 
     if let Ok(record) = prompt::select(record_s, "Select record:") {
         let record = record_map.get_mut(&record).unwrap();
 
         if let Ok(begin_time) =
-            prompt::text_input_with_default("Begin time", &record.begin.to_string_hm())
+            prompt::text_input_with_default("Begin time", &format_time_on(&date, &record.begin))
         {
-            record.begin = TaskTime::parse_with_date(&date, &begin_time)?;
+            record.begin = TaskTime::parse_with_date_or_datetime(&date, &begin_time)?;
         };
 
-        let end = match record.end.clone() {
-            Some(time) => time.to_string_hm(),
+        let end = match &record.end {
+            Some(time) => format_time_on(&date, time),
             None => "".to_string(),
         };
         if let Ok(end_time) = prompt::text_input_with_default("End time", &end) {
-            record.end = Some(TaskTime::parse_with_date(&date, &end_time)?);
+            record.end = Some(TaskTime::parse_with_date_or_datetime(&date, &end_time)?);
+        };
+
+        let note = record.note.clone().unwrap_or_default();
+        if let Ok(note) = prompt::text_input_with_default("Note", &note) {
+            record.note = Some(note).filter(|note| !note.is_empty());
+        };
+        if let Ok(tags) = prompt::text_input_with_default("Tags", &record.tags.join(", ")) {
+            record.tags = parse_tags(&tags);
         };
         db.add_record(record)?;
         // show records
@@ -46,3 +89,52 @@ pub fn run(
     };
     Ok(())
 }
+
+/// Shifts begin and end times of all records of the date by `offset` (e.g. `+30m`).
+///
+/// Fails without changes if a shifted record would leave the working date.
+pub fn shift(
+    db: &SQLiteDatabase,
+    date: Option<WorkingDate>,
+    offset: &str,
+    confirm_days: i64,
+    mut writer: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let date = date.unwrap_or_else(WorkingDate::today);
+    let delta = parse_shift(offset).map_err(|e| CliError::invalid("offset", offset, e))?;
+    if !confirm_date(&date, confirm_days)? {
+        return Ok(());
+    }
+
+    let mut records = db.get_records_by_date(&date)?;
+    if records.is_empty() {
+        writeln!(writer, "No Records")?;
+        return Ok(());
+    }
+
+    for record in records.iter_mut() {
+        record.begin = record.begin.shift(delta);
+        record.end = record.end.as_ref().map(|end| end.shift(delta));
+
+        let out_of_day = WorkingDate::from(record.begin.clone()) != date
+            || record
+                .end
+                .as_ref()
+                .is_some_and(|end| end > &date.end_of_day());
+        if out_of_day {
+            return Err(format!(
+                "shifting by {} moves record {} out of {}",
+                offset,
+                record.id.unwrap_or_default(),
+                date
+            )
+            .into());
+        }
+    }
+
+    db.transaction(|db| records.iter().try_for_each(|record| db.add_record(record)))?;
+
+    let records = db.get_records_by_date(&date)?;
+    writeln!(writer, "{}", table::record_list(&records))?;
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::io::{read_to_string, stdin, Write};
+use std::path::Path;
+
+use shigotolog::export::parse_csv_lenient;
+use shigotolog::sqlite_db::SQLiteDatabase;
+
+/// Imports records from CSV made by `export`. Reads stdin if `path` is `-`.
+///
+/// Rows that cannot be parsed are skipped and reported with their line numbers.
+pub fn run(db: &SQLiteDatabase, path: &Path, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let input = if path == Path::new("-") {
+        read_to_string(stdin())?
+    } else {
+        std::fs::read_to_string(path)?
+    };
+
+    let (records, skipped) = parse_csv_lenient(&input)?;
+    let count = db.import_records(&records)?;
+    let tasks = records
+        .iter()
+        .map(|record| (record.task.format_name("/"), &record.task.description))
+        .collect::<HashSet<_>>();
+
+    writeln!(
+        writer,
+        "Imported {} records across {} tasks; {} rows skipped.",
+        count,
+        tasks.len(),
+        skipped.len()
+    )?;
+    for (line, reason) in &skipped {
+        writeln!(writer, "  line {}: {}", line, reason)?;
+    }
+    Ok(())
+}
@@ -1,48 +1,329 @@
+use std::collections::HashSet;
 use std::error::Error;
-use std::io::Write;
+use std::io::{stderr, IsTerminal, Write};
 
-use shigotolog::datetime::WorkingDate;
+use chrono::{TimeDelta, Weekday};
+
+use shigotolog::datetime::{is_working_day, TimeDisplay, WorkingDate};
 use shigotolog::repository::Manipulation;
 use shigotolog::sqlite_db::SQLiteDatabase;
+use shigotolog::task::{TaskRecord, TaskSummary};
 
+use crate::error::CliError;
+use crate::format::{CsvFormatter, JsonFormatter, RecordFormat, RecordFormatter, TableFormatter};
+use crate::prompt;
 use crate::table;
+use crate::util::map_tasks;
+
+/// Options for printing records.
+#[derive(Debug, Default)]
+pub struct LogOptions {
+    /// Target date
+    pub date: Option<WorkingDate>,
+    /// Target month (`YYYY-MM`)
+    pub month: Option<String>,
+    /// Target ISO week (`YYYY-Www`)
+    pub isoweek: Option<String>,
+    /// Start of the target period, defaults to the earliest record
+    pub from: Option<WorkingDate>,
+    /// End of the target period, defaults to today
+    pub to: Option<WorkingDate>,
+    /// Print all records
+    pub show_all: bool,
+    /// Print records in newest-first order
+    pub reverse: bool,
+    /// Print record ids
+    pub show_id: bool,
+    /// Number of task levels to show, all levels if `None`
+    pub depth: Option<usize>,
+    /// Decimal places of percentages
+    pub percent_precision: u32,
+    /// Deduct break time within work records from the total duration
+    pub deduct_breaks: bool,
+    /// Insert rows for unlogged time between records
+    pub show_gaps: bool,
+    /// Print only records with the tag
+    pub tag: Option<String>,
+    /// Leave out records of unregistered tasks
+    pub hide_inactive: bool,
+    /// Select a task and print only its records
+    pub select_task: bool,
+    /// Print dates in the period with no records instead of records
+    pub empty_days: bool,
+    /// Leave weekends out of empty days
+    pub skip_weekends: bool,
+    /// Days of the week not expected to be worked
+    pub weekend: Vec<Weekday>,
+    /// Time window of each day (`HH:MM-HH:MM`) to clip records to
+    pub between: Option<String>,
+    /// Longest gap between records of a task in one session, no session counts if `None`
+    pub group_gap: Option<TimeDelta>,
+    /// Width of bar charts of durations by task, no charts if `None`
+    pub bar_width: Option<usize>,
+    /// Print only the total worked time
+    pub sum_only: bool,
+    /// Format of records; summaries are printed only with tables
+    pub format: RecordFormat,
+    /// Show begin and end times as stored in tables
+    pub raw: bool,
+}
 
 pub fn run(
     db: &SQLiteDatabase,
-    date: Option<String>,
-    month: Option<String>,
-    show_all: bool,
+    options: LogOptions,
     mut writer: impl Write,
 ) -> Result<(), Box<dyn Error>> {
-    let records = if show_all {
+    let LogOptions {
+        date,
+        month,
+        isoweek,
+        from,
+        to,
+        show_all,
+        reverse,
+        show_id,
+        depth,
+        percent_precision,
+        deduct_breaks,
+        show_gaps,
+        tag,
+        hide_inactive,
+        select_task,
+        empty_days,
+        skip_weekends,
+        weekend,
+        between,
+        group_gap,
+        bar_width,
+        sum_only,
+        format,
+        raw,
+    } = options;
+
+    let period = if let Some(arg_yearmonth) = &month {
+        Some(WorkingDate::parse_ym(arg_yearmonth)?)
+    } else if let Some(arg_isoweek) = &isoweek {
+        Some(WorkingDate::parse_isoweek(arg_isoweek)?)
+    } else if from.is_some() || to.is_some() {
+        let en = to.clone().unwrap_or_else(WorkingDate::today);
+        let st = match &from {
+            Some(from) => from.clone(),
+            None => db.earliest_date()?.unwrap_or_else(|| en.clone()),
+        };
+        Some((st, en))
+    } else {
+        None
+    };
+
+    let mut records = if select_task {
+        let tasks = db.tasks()?;
+        let (task_map, keys) = map_tasks(tasks, true);
+        let Ok(key) = prompt::select(keys, "Select task:") else {
+            return Ok(());
+        };
+        let task_id = task_map[&key].id.ok_or("task has no id")?;
+        let mut records = db.records_for_task(task_id)?;
+        if !show_all {
+            let (st, en) = match (&date, &period) {
+                (Some(date), _) => (date.clone(), date.clone()),
+                (None, Some(period)) => period.clone(),
+                (None, None) => (WorkingDate::today(), WorkingDate::today()),
+            };
+            records.retain(|record| st <= record.working_date && record.working_date <= en);
+        }
+        records
+    } else if show_all {
         db.records()?
-    } else if let Some(arg_date) = &date {
-        db.get_records_by_date(&WorkingDate::parse(arg_date)?)?
-    } else if let Some(arg_yearmonth) = &month {
-        let (st, en) = WorkingDate::parse_ym(arg_yearmonth)?;
-        db.get_records_in_period(&st, &en)?
+    } else if let Some(date) = &date {
+        db.get_records_by_date(date)?
+    } else if let Some((st, en)) = &period {
+        db.get_records_in_period(st, en)?
     } else {
         db.get_records_by_date(&WorkingDate::today())?
     };
+    if let Some(tag) = &tag {
+        records.retain(|record| record.has_tag(tag));
+    }
+    if hide_inactive {
+        records.retain(|record| record.task.is_active);
+    }
+    if let Some(window) = &between {
+        let mut clipped = Vec::with_capacity(records.len());
+        for record in &records {
+            let (from, to) = record
+                .working_date
+                .parse_window(window)
+                .map_err(|e| CliError::invalid("window", window, e))?;
+            clipped.extend(record.clip(&from, &to));
+        }
+        records = clipped;
+    }
 
-    write!(writer, "{}", table::record_list(&records))?;
-    if !show_all && month.is_none() {
-        let task_summary_table = table::task_summary(&records);
+    if sum_only {
+        writeln!(
+            writer,
+            "{}",
+            worked_duration(&records, deduct_breaks).to_string_hm()
+        )?;
+        return Ok(());
+    }
+
+    if empty_days {
+        let (st, en) =
+            period.ok_or("--empty-days needs a period (--month, --isoweek, --from or --to)")?;
+        // days to come are not missing yet
+        let en = en.min(WorkingDate::today());
+        let logged = records
+            .iter()
+            .map(|record| &record.working_date)
+            .collect::<HashSet<_>>();
+        let dates = st
+            .iter_days(&en)
+            .filter(|date| !logged.contains(date))
+            .filter(|date| !skip_weekends || is_working_day(date, &weekend, &[]))
+            .collect::<Vec<_>>();
+
+        if dates.is_empty() {
+            writeln!(writer, "No Empty Days")?;
+        }
+        for date in &dates {
+            writeln!(writer, "{} {}", date.to_string_display(), date.format("%a"))?;
+        }
+        return Ok(());
+    }
+
+    let is_period = period.is_some();
+
+    // rendering all records may take a while, so show progress on the terminal
+    let show_progress = show_all && stderr().is_terminal();
+    if show_progress {
+        write!(stderr(), "Rendering {} records...", records.len())?;
+    }
+
+    let formatter: Box<dyn RecordFormatter> = match format {
+        RecordFormat::Table => Box::new(TableFormatter {
+            show_id,
+            depth,
+            show_gaps,
+            raw,
+        }),
+        RecordFormat::Csv => Box::new(CsvFormatter { header: true }),
+        RecordFormat::Json => Box::new(JsonFormatter),
+    };
+    // summaries below rely on the chronological order, and never count gap rows
+    let mut formatted = vec![];
+    if reverse {
+        let reversed = records.iter().rev().cloned().collect::<Vec<_>>();
+        formatter.write_records(&reversed, &mut formatted)?;
+    } else {
+        formatter.write_records(&records, &mut formatted)?;
+    }
+
+    if show_progress {
+        // clear the progress line
+        write!(stderr(), "\r\x1b[2K")?;
+    }
+
+    writer.write_all(&formatted)?;
+    if format != RecordFormat::Table {
+        return Ok(());
+    }
+    if !show_all && !is_period {
+        let task_summary_table = table::task_summary(&records, deduct_breaks);
         if !task_summary_table.is_empty() {
             write!(writer, "\n\n Summary\n{}", task_summary_table)?;
         }
 
-        let task_durations_table = table::task_durations(&records);
+        let task_durations_table = table::task_durations_with_depth(
+            &records,
+            depth,
+            percent_precision,
+            group_gap,
+            bar_width,
+        );
         if !task_durations_table.is_empty() {
             write!(writer, "\n{}", task_durations_table)?;
         }
 
+        let tag_durations_table = table::tag_durations(&records, percent_precision);
+        if !tag_durations_table.is_empty() {
+            write!(writer, "\n\n Tags\n{}", tag_durations_table)?;
+        }
+
+        let session_list_table = table::session_list(&records);
+        if !session_list_table.is_empty() {
+            write!(writer, "\n\n Sessions\n{}", session_list_table)?;
+        }
+
         let break_times_table = table::break_times(&records);
         if !break_times_table.is_empty() {
             write!(writer, "\n\n Break\n{}", break_times_table)?;
         }
-    } else if month.is_some() {
-        write!(writer, "\n\n Summary\n{}", table::task_durations(&records))?;
+    } else if is_period {
+        write!(
+            writer,
+            "\n\n Summary\n{}",
+            table::task_durations_with_depth(
+                &records,
+                depth,
+                percent_precision,
+                group_gap,
+                bar_width
+            )
+        )?;
+
+        let tag_durations_table = table::tag_durations(&records, percent_precision);
+        if !tag_durations_table.is_empty() {
+            write!(writer, "\n\n Tags\n{}", tag_durations_table)?;
+        }
     }
     Ok(())
 }
+
+/// Total duration of work records, zero if there are none such as on a day with breaks only.
+fn worked_duration(records: &[TaskRecord], deduct_breaks: bool) -> TimeDelta {
+    TaskSummary::new(records).map_or(TimeDelta::zero(), |summary| match deduct_breaks {
+        true => summary.net_duration(),
+        false => summary.total_duration,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use shigotolog::datetime::TaskTime;
+    use shigotolog::task::Task;
+
+    #[test]
+    fn test_worked_duration() {
+        let task = Task::new(None, Some("a"), None, None, "", false, true);
+        let break_task = Task::new(None, Some("lunch"), None, None, "", true, true);
+        let record = |task: &Task, begin: &str, end: &str| {
+            let begin = TaskTime::parse(begin).unwrap();
+            TaskRecord::new(
+                None,
+                task.clone(),
+                WorkingDate::from(begin.clone()),
+                begin,
+                Some(TaskTime::parse(end).unwrap()),
+            )
+        };
+
+        assert_eq!(worked_duration(&[], false), TimeDelta::zero());
+
+        // breaks only
+        let breaks = [record(
+            &break_task,
+            "2024-03-04T12:00:00",
+            "2024-03-04T13:00:00",
+        )];
+        assert_eq!(worked_duration(&breaks, false), TimeDelta::zero());
+        assert_eq!(worked_duration(&breaks, true), TimeDelta::zero());
+
+        let work = record(&task, "2024-03-04T09:00:00", "2024-03-04T17:00:00");
+        let records = [work, breaks[0].clone()];
+        assert_eq!(worked_duration(&records, false), TimeDelta::hours(8));
+        assert_eq!(worked_duration(&records, true), TimeDelta::hours(7));
+    }
+}
@@ -0,0 +1,82 @@
+use std::error::Error;
+use std::io::{stderr, Write};
+
+use shigotolog::datetime::{parse_duration, TaskTime, TimeDisplay, WorkingDate};
+use shigotolog::repository::Manipulation;
+use shigotolog::sqlite_db::SQLiteDatabase;
+use shigotolog::task::{parse_tags, TaskRecord};
+
+use crate::error::CliError;
+use crate::prompt;
+use crate::table;
+use crate::util::{map_tasks, NO_TASKS};
+
+/// Adds a record of the selected task lasting `duration` (e.g. `90m`, `1h30m`).
+///
+/// The begin time defaults to the end of the last record of the date, or `duration` before now
+/// if there is none.
+pub fn run(
+    db: &SQLiteDatabase,
+    date: Option<WorkingDate>,
+    duration: &str,
+    mut writer: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let date = date.unwrap_or_else(WorkingDate::today);
+    let delta = parse_duration(duration).map_err(|e| CliError::invalid("duration", duration, e))?;
+
+    let records = db.get_records_by_date(&date)?;
+    let default_begin = records
+        .iter()
+        .filter_map(|record| record.end.clone())
+        .max()
+        .unwrap_or_else(|| TaskTime::now().shift(-delta));
+
+    let tasks = db.tasks()?;
+    let (task_map, keys) = map_tasks(tasks, false);
+    if keys.is_empty() {
+        writeln!(stderr(), "{}", NO_TASKS)?;
+        return Ok(());
+    }
+    let Ok(key) = prompt::select(keys, "Select task:") else {
+        return Ok(());
+    };
+    let task = task_map.get(&key).unwrap().clone();
+
+    let Ok(begin_hm) =
+        prompt::text_input_with_default("Begin time:", &default_begin.to_string_hm())
+    else {
+        return Ok(());
+    };
+    let begin = TaskTime::parse_with_date(&date, &begin_hm)?;
+    let end = begin.shift(delta);
+    if WorkingDate::from(end.clone()) != date {
+        return Err(format!(
+            "end time {} is past the working date",
+            end.to_string_display()
+        )
+        .into());
+    }
+
+    let mut record = TaskRecord::new(None, task, date.clone(), begin, Some(end));
+    if records.iter().any(|other| record.is_duplicate_of(other)) {
+        writeln!(
+            stderr(),
+            "{} is already logged at {}. Not added.",
+            record.task.format_name("/"),
+            record.begin.to_string_display()
+        )?;
+        return Ok(());
+    }
+    if let Ok(note) = prompt::text_input("Note:") {
+        record.note = Some(note).filter(|note| !note.is_empty());
+    }
+    if let Ok(tags) = prompt::text_input("Tags (comma separated):") {
+        record.tags = parse_tags(&tags);
+    }
+    db.add_record(&record)?;
+
+    // show records
+    let records = db.get_records_by_date(&date)?;
+    writeln!(writer, "{}", table::record_list(&records))?;
+    Ok(())
+}
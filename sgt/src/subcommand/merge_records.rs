@@ -0,0 +1,38 @@
+use std::error::Error;
+use std::io::Write;
+
+use shigotolog::datetime::WorkingDate;
+use shigotolog::repository::Manipulation;
+use shigotolog::sqlite_db::SQLiteDatabase;
+use shigotolog::task::merge_adjacent;
+
+use crate::table;
+
+pub fn run(
+    db: &SQLiteDatabase,
+    date: Option<WorkingDate>,
+    mut writer: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let date = date.unwrap_or_else(WorkingDate::today);
+
+    let records = db.get_records_by_date(&date)?;
+    let (updated, deleted) = merge_adjacent(&records);
+
+    db.transaction(|db| {
+        for record in &updated {
+            db.add_record(record)?;
+        }
+        for id in &deleted {
+            db.delete_record(*id)?;
+        }
+        Ok(())
+    })?;
+
+    writeln!(writer, "Merged {} records.", deleted.len())?;
+    if !deleted.is_empty() {
+        // show records
+        let records = db.get_records_by_date(&date)?;
+        writeln!(writer, "{}", table::record_list(&records))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,10 @@
+use std::error::Error;
+use std::io::Write;
+
+use shigotolog::sqlite_db::SQLiteDatabase;
+
+pub fn run(db: &SQLiteDatabase, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let count = db.normalize_records()?;
+    writeln!(writer, "Normalized {} records.", count)?;
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use std::error::Error;
+use std::io::Write;
+
+use shigotolog::datetime::{TaskTime, WorkingDate};
+use shigotolog::repository::{Manipulation, State};
+use shigotolog::sqlite_db::SQLiteDatabase;
+
+use crate::subcommand::start::close_stale_record;
+use crate::table;
+
+/// Ends the task running today now, keeping it as a session to be resumed by `resume`.
+///
+/// A record left open on a past day is offered to be closed as in `start`, since pausing it
+/// now would make it span days.
+pub fn run(db: &SQLiteDatabase, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let date = WorkingDate::today();
+    if let Some(open_record) = db.latest_open_record()? {
+        if open_record.working_date < date {
+            close_stale_record(db, open_record, &date)?;
+        }
+    }
+
+    let State::Active(mut record) = db.current_state(&date)? else {
+        return Err("No active task to pause".into());
+    };
+
+    record.end = Some(TaskTime::now());
+    // a session is identified by its first record
+    record.session_id = record.session_id.or(record.id);
+    db.add_record(&record)?;
+
+    let records = db.get_records_by_date(&record.working_date)?;
+    writeln!(writer, "{}", table::record_list(&records))?;
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use std::error::Error;
+use std::io::Write;
+
+use shigotolog::datetime::{TaskTime, WorkingDate};
+use shigotolog::repository::{Manipulation, State};
+use shigotolog::sqlite_db::SQLiteDatabase;
+use shigotolog::task::TaskRecord;
+
+use crate::subcommand::start::close_stale_record;
+use crate::table;
+
+/// Starts a new record of the latest paused session now, in the same session.
+///
+/// The record running today, if any, is ended at the same time. A record left open on a past
+/// day is offered to be closed as in `start`, rather than being ended now across days.
+pub fn run(db: &SQLiteDatabase, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let date = WorkingDate::today();
+    if let Some(open_record) = db.latest_open_record()? {
+        if open_record.working_date < date {
+            close_stale_record(db, open_record, &date)?;
+        }
+    }
+
+    let paused = db
+        .latest_session_record()?
+        .ok_or("No paused task to resume")?;
+    if paused.end.is_none() {
+        return Err("The session is not paused".into());
+    }
+
+    let now = TaskTime::now();
+    let mut record = TaskRecord::new(None, paused.task, date.clone(), now.clone(), None);
+    record.tags = paused.tags;
+    record.session_id = paused.session_id;
+
+    let active = match db.current_state(&date)? {
+        State::Active(record) => Some(record),
+        _ => None,
+    };
+    db.transaction(|db| {
+        if let Some(mut active) = active {
+            active.end = Some(now);
+            db.add_record(&active)?;
+        }
+        db.add_record(&record)
+    })?;
+
+    let records = db.get_records_by_date(&date)?;
+    writeln!(writer, "{}", table::record_list(&records))?;
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use std::error::Error;
+use std::io::Write;
+
+use shigotolog::datetime::TaskTime;
+use shigotolog::repository::Manipulation;
+use shigotolog::sqlite_db::SQLiteDatabase;
+
+use crate::prompt;
+use crate::table;
+use crate::util::map_tasks_with_current;
+
+pub fn run(
+    db: &SQLiteDatabase,
+    id: u32,
+    time: &str,
+    mut writer: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let record = db
+        .get_record(id)
+        .map_err(|_| format!("No record with id {}", id))?;
+    let date = record.working_date.clone();
+
+    let at = TaskTime::parse_with_date(&date, time)?;
+    let (first, mut second) = record
+        .split_at(&at)
+        .ok_or("split time must be between begin and end of the record")?;
+
+    let tasks = db.tasks()?;
+    let (task_map, keys, cursor) = map_tasks_with_current(tasks, &record.task);
+    if let Ok(key) = prompt::select_with_cursor(keys, "Task after the split:", cursor) {
+        second.task = task_map.get(&key).unwrap().clone();
+
+        db.transaction(|db| {
+            db.add_record(&first)?;
+            db.add_record(&second)
+        })?;
+        // show records
+        let records = db.get_records_by_date(&date)?;
+        writeln!(
+            writer,
+            "{}",
+            table::record_list_with_options(&records, true, None, false, false)
+        )?;
+    }
+    Ok(())
+}
@@ -1,43 +1,116 @@
 use std::error::Error;
-use std::io::Write;
+use std::io::{stderr, Write};
+
+use chrono::TimeDelta;
 
 use shigotolog::datetime::{TaskTime, TimeDisplay, WorkingDate};
 use shigotolog::repository::{Manipulation, State};
 use shigotolog::sqlite_db::SQLiteDatabase;
-use shigotolog::task::TaskRecord;
+use shigotolog::task::{parse_tags, Task, TaskRecord};
 
 use crate::prompt;
 use crate::table;
-use crate::util::map_tasks;
+use crate::util::{map_tasks, NO_TASKS};
+
+/// Begin times later than now by more than this need confirmation.
+const FUTURE_TOLERANCE: TimeDelta = TimeDelta::minutes(5);
 
+/// Starts a task, ending the active record of the date.
+///
+/// The task is `task` if given, the task of the last record with `use_last`, or selected
+/// on the prompt otherwise.
 pub fn run(
     db: &SQLiteDatabase,
-    date: Option<String>,
+    date: Option<WorkingDate>,
+    task: Option<Task>,
+    use_last: bool,
+    use_now: bool,
     mut writer: impl Write,
 ) -> Result<(), Box<dyn Error>> {
-    let date = if let Some(date) = date {
-        WorkingDate::parse(&date)?
-    } else {
-        WorkingDate::today()
-    };
+    let date = date.unwrap_or_else(WorkingDate::today);
+
+    if let Some(open_record) = db.latest_open_record()? {
+        if open_record.working_date < date {
+            close_stale_record(db, open_record, &date)?;
+        }
+    }
 
     let current_time = TaskTime::now();
     let state = db.current_state(&date)?;
-    let tasks = db.tasks()?;
-    let (task_map, keys) = map_tasks(tasks);
+    let preselected = match task {
+        Some(task) => Some(task),
+        None if use_last => db.records()?.pop().map(|record| record.task),
+        None => None,
+    };
+
+    let task = match preselected {
+        Some(task) => Some(task),
+        None => {
+            let tasks = db.tasks()?;
+            let (task_map, keys) = map_tasks(tasks, false);
+            if keys.is_empty() {
+                writeln!(stderr(), "{}", NO_TASKS)?;
+                return Ok(());
+            }
+            prompt::select(keys, "Select task:")
+                .ok()
+                .map(|key| task_map.get(&key).unwrap().clone())
+        }
+    };
 
-    if let Ok(key) = prompt::select(keys, "Select task:") {
-        let task = task_map.get(&key).unwrap();
-        if let Ok(begin_hm) =
+    if let Some(task) = task {
+        let begin_hm = if use_now {
+            Ok(current_time.to_string_hm())
+        } else {
             prompt::text_input_with_default("Begin time:", &current_time.to_string_hm())
-        {
+        };
+        if let Ok(begin_hm) = begin_hm {
             let begin = TaskTime::parse_with_date(&date, &begin_hm)?;
-            if let State::Active(mut last_record) = state {
-                last_record.end = Some(begin.clone());
-                db.add_record(&last_record)?;
+            if &begin - &current_time > FUTURE_TOLERANCE {
+                let message = format!(
+                    "Begin time {} is in the future. Continue?",
+                    begin.to_string_display()
+                );
+                if !prompt::confirm(&message, false).unwrap_or(false) {
+                    return Ok(());
+                }
             }
-            let record = TaskRecord::new(None, task.clone(), date.clone(), begin, None);
-            db.add_record(&record)?;
+            let mut record = TaskRecord::new(None, task, date.clone(), begin.clone(), None);
+            // running start twice in a minute would otherwise log the task twice
+            let records = db.get_records_by_date(&date)?;
+            if records.iter().any(|other| record.is_duplicate_of(other)) {
+                writeln!(
+                    stderr(),
+                    "{} is already logged at {}. Not added.",
+                    record.task.format_name("/"),
+                    begin.to_string_display()
+                )?;
+                return Ok(());
+            }
+            // recurring work often has the same note as last time
+            let last_note = match record.task.id {
+                Some(task_id) => db
+                    .records_for_task(task_id)?
+                    .pop()
+                    .and_then(|record| record.note),
+                None => None,
+            };
+            let note =
+                prompt::text_input_with_initial_value("Note:", &last_note.unwrap_or_default());
+            if let Ok(note) = note {
+                record.note = Some(note).filter(|note| !note.is_empty());
+            }
+            if let Ok(tags) = prompt::text_input("Tags (comma separated):") {
+                record.tags = parse_tags(&tags);
+            }
+            // close the active record and add the new one at once
+            db.transaction(|db| {
+                if let State::Active(mut last_record) = state {
+                    last_record.end = Some(begin);
+                    db.add_record(&last_record)?;
+                }
+                db.add_record(&record)
+            })?;
             // show records
             let records = db.get_records_by_date(&date)?;
             writeln!(writer, "{}", table::record_list(&records))?;
@@ -45,3 +118,39 @@ pub fn run(
     }
     Ok(())
 }
+
+/// Offers to close a record left open on a working date before `date` at the end of its day.
+///
+/// Records left open for more than a day are likely to need a different end time,
+/// so closing them is not the default.
+pub fn close_stale_record(
+    db: &SQLiteDatabase,
+    mut record: TaskRecord,
+    date: &WorkingDate,
+) -> Result<(), Box<dyn Error>> {
+    let days = date.days_since(&record.working_date);
+    let end = record.working_date.end_of_day();
+    let message = if days == 1 {
+        format!(
+            "{} started at {} yesterday is not ended. Close it at the end of the day?",
+            record.task.format_name("/"),
+            record.begin.to_string_display(),
+        )
+    } else {
+        format!(
+            "{} started at {} on {} ({} days ago) is not ended. Close it at the end of that day?",
+            record.task.format_name("/"),
+            record.begin.to_string_display(),
+            record.working_date.to_string_display(),
+            days,
+        )
+    };
+
+    if prompt::confirm(&message, days == 1).unwrap_or(false) {
+        record.end = Some(end);
+        db.add_record(&record)?;
+    } else if let Some(id) = record.id {
+        writeln!(stderr(), "Run `sgt edit {}` to set the end time.", id)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use std::error::Error;
+use std::io::{stderr, Write};
+use std::thread::sleep;
+use std::time::Duration;
+
+use chrono::TimeDelta;
+
+use shigotolog::datetime::{parse_duration, TimeDisplay, WorkingDate};
+use shigotolog::repository::{Manipulation, State};
+use shigotolog::sqlite_db::SQLiteDatabase;
+use shigotolog::task::TaskSummary;
+
+use crate::error::CliError;
+use crate::util::warn_open_record;
+
+/// Prints the active task and the worked time of today, refreshed every `watch` seconds
+/// until interrupted if set.
+pub fn run(
+    db: &SQLiteDatabase,
+    target: Option<String>,
+    porcelain: bool,
+    watch: Option<u64>,
+    mut writer: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let date = WorkingDate::today();
+    let target = target
+        .as_deref()
+        .map(|s| parse_duration(s).map_err(|e| CliError::invalid("target", s, e)))
+        .transpose()?;
+
+    if porcelain {
+        return write_porcelain(db, &date, writer);
+    }
+
+    warn_open_record(db, &date, stderr())?;
+
+    if let Some(interval) = watch {
+        return watch_status(db, target, Duration::from_secs(interval), writer);
+    }
+
+    match db.current_state(&date)? {
+        State::Active(record) => writeln!(
+            writer,
+            "Active: {} (since {}, {})",
+            record.task.format_name("/"),
+            record.begin.to_string_display(),
+            record.duration().to_string_hm()
+        )?,
+        State::Completed => writeln!(writer, "No active task")?,
+    }
+
+    writeln!(writer, "{}", worked_text(db, &date, target)?)?;
+    Ok(())
+}
+
+/// Rewrites the status in a single line every `interval`.
+///
+/// The cursor and the terminal mode are left as they are, so that interrupting with Ctrl-C
+/// needs no cleanup.
+fn watch_status(
+    db: &SQLiteDatabase,
+    target: Option<TimeDelta>,
+    interval: Duration,
+    mut writer: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    loop {
+        // the working date changes while watching overnight
+        let date = WorkingDate::today();
+        let active = match db.current_state(&date)? {
+            State::Active(record) => format!(
+                "Active: {} (since {}, {})",
+                record.task.format_name("/"),
+                record.begin.to_string_display(),
+                record.duration().to_string_hm()
+            ),
+            State::Completed => "No active task".into(),
+        };
+        // clear the line before writing, as it may be shorter than the previous one
+        write!(
+            writer,
+            "\r\x1b[2K{} | {}",
+            active,
+            worked_text(db, &date, target)?
+        )?;
+        writer.flush()?;
+        sleep(interval);
+    }
+}
+
+/// Describes the worked time of the date, with the progress toward `target` if given.
+fn worked_text(
+    db: &SQLiteDatabase,
+    date: &WorkingDate,
+    target: Option<TimeDelta>,
+) -> Result<String, Box<dyn Error>> {
+    let records = db.get_records_by_date(date)?;
+    let worked =
+        TaskSummary::new(&records).map_or(TimeDelta::zero(), |summary| summary.total_duration);
+
+    let text = match target {
+        Some(target) => {
+            let rest = target - worked;
+            let progress = if rest < TimeDelta::zero() {
+                format!("{} over", (-rest).to_string_hm())
+            } else {
+                format!("{} remaining", rest.to_string_hm())
+            };
+            format!(
+                "Worked {} / {} ({})",
+                worked.to_string_hm(),
+                target.to_string_hm(),
+                progress
+            )
+        }
+        None => format!("Worked {}", worked.to_string_hm()),
+    };
+    Ok(text)
+}
+
+/// Prints the state in a single line for scripts. Fields are separated by tabs:
+/// `active<TAB>task<TAB>begin<TAB>elapsed minutes`, or `idle`.
+fn write_porcelain(
+    db: &SQLiteDatabase,
+    date: &WorkingDate,
+    mut writer: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    match db.current_state(date)? {
+        State::Active(record) => writeln!(
+            writer,
+            "active\t{}\t{}\t{}",
+            record.task.format_name_or("/", "-"),
+            record.begin.to_string_hm(),
+            record.duration().num_minutes()
+        )?,
+        State::Completed => writeln!(writer, "idle")?,
+    }
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use std::error::Error;
+use std::io::Write;
+
+use chrono::TimeDelta;
+
+use shigotolog::datetime::WorkingDate;
+use shigotolog::repository::Manipulation;
+use shigotolog::sqlite_db::SQLiteDatabase;
+
+use crate::table;
+
+/// Options for printing durations by task.
+#[derive(Debug, Default)]
+pub struct SummaryOptions {
+    /// Target date
+    pub date: Option<WorkingDate>,
+    /// Target month (`YYYY-MM`)
+    pub month: Option<String>,
+    /// Target ISO week (`YYYY-Www`)
+    pub isoweek: Option<String>,
+    /// Target the month to date
+    pub mtd: bool,
+    /// Target the year to date
+    pub ytd: bool,
+    /// Number of task levels to group durations by, all levels if `None`
+    pub depth: Option<usize>,
+    /// Decimal places of percentages
+    pub percent_precision: u32,
+    /// Longest gap between records of a task in one session, no session counts if `None`
+    pub group_gap: Option<TimeDelta>,
+    /// Width of bar charts of durations by task, no charts if `None`
+    pub bar_width: Option<usize>,
+    /// Leave out records of unregistered tasks
+    pub hide_inactive: bool,
+    /// Split the total into billable and non-billable durations
+    pub billable: bool,
+}
+
+pub fn run(
+    db: &SQLiteDatabase,
+    options: SummaryOptions,
+    mut writer: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let SummaryOptions {
+        date,
+        month,
+        isoweek,
+        mtd,
+        ytd,
+        depth,
+        percent_precision,
+        group_gap,
+        bar_width,
+        hide_inactive,
+        billable,
+    } = options;
+
+    let mut records = if let Some(date) = &date {
+        db.get_records_by_date(date)?
+    } else if let Some(arg_yearmonth) = &month {
+        let (st, en) = WorkingDate::parse_ym(arg_yearmonth)?;
+        db.get_records_in_period(&st, &en)?
+    } else if let Some(arg_isoweek) = &isoweek {
+        let (st, en) = WorkingDate::parse_isoweek(arg_isoweek)?;
+        db.get_records_in_period(&st, &en)?
+    } else if mtd {
+        let today = WorkingDate::today();
+        db.get_records_in_period(&today.containing_month().0, &today)?
+    } else if ytd {
+        let today = WorkingDate::today();
+        db.get_records_in_period(&today.year_start(), &today)?
+    } else {
+        db.get_records_by_date(&WorkingDate::today())?
+    };
+    if hide_inactive {
+        records.retain(|record| record.task.is_active);
+    }
+
+    let task_durations_table =
+        table::task_durations_with_depth(&records, depth, percent_precision, group_gap, bar_width);
+    if task_durations_table.is_empty() {
+        writeln!(writer, "No Records")?;
+    } else {
+        writeln!(writer, "{}", task_durations_table)?;
+    }
+
+    if billable {
+        let billable_durations_table = table::billable_durations(&records, percent_precision);
+        if !billable_durations_table.is_empty() {
+            writeln!(writer, "\n Billable\n{}", billable_durations_table)?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints total and average durations by day of the week in the month or the ISO week.
+///
+/// The month defaults to this month.
+pub fn by_weekday(
+    db: &SQLiteDatabase,
+    month: Option<String>,
+    isoweek: Option<String>,
+    mut writer: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let (st, en) = match (&month, &isoweek) {
+        (_, Some(arg_isoweek)) => WorkingDate::parse_isoweek(arg_isoweek)?,
+        (Some(arg_yearmonth), None) => WorkingDate::parse_ym(arg_yearmonth)?,
+        (None, None) => WorkingDate::today().containing_month(),
+    };
+    let records = db.get_records_in_period(&st, &en)?;
+
+    let weekday_durations_table = table::weekday_durations(&records);
+    if weekday_durations_table.is_empty() {
+        writeln!(writer, "No Records")?;
+    } else {
+        writeln!(writer, "{}", weekday_durations_table)?;
+    }
+    Ok(())
+}
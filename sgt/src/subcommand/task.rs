@@ -1,3 +1,6 @@
+pub mod levels;
 pub mod ls;
+pub mod reactivate;
 pub mod register;
+pub mod total;
 pub mod unregister;
@@ -0,0 +1,23 @@
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::io::Write;
+
+use shigotolog::repository::Manipulation;
+use shigotolog::sqlite_db::SQLiteDatabase;
+
+pub fn run(
+    db: &SQLiteDatabase,
+    level: usize,
+    mut writer: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let tasks = db.tasks()?;
+    let values = tasks
+        .iter()
+        .filter_map(|task| task.task[level - 1].as_deref())
+        .collect::<BTreeSet<_>>();
+
+    for value in values {
+        writeln!(writer, "{}", value)?;
+    }
+    Ok(())
+}
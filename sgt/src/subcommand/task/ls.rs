@@ -1,17 +1,46 @@
+use std::cmp::Reverse;
 use std::error::Error;
 use std::io::Write;
 
+use clap::ValueEnum;
+
 use shigotolog::repository::Manipulation;
 use shigotolog::sqlite_db::SQLiteDatabase;
 
 use crate::table;
 
+/// Order of listed tasks.
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum TaskSort {
+    /// By task names
+    #[default]
+    Name,
+    /// Active tasks first
+    Active,
+    /// Most logged tasks first
+    Usage,
+}
+
 pub fn run(
     db: &SQLiteDatabase,
     show_all: bool,
+    sort: TaskSort,
     mut writer: impl Write,
 ) -> Result<(), Box<dyn Error>> {
+    // sorted by names
     let mut tasks = db.tasks()?;
+    let total_count = db.task_count()?;
+
+    match sort {
+        TaskSort::Name => {}
+        TaskSort::Active => tasks.sort_by_key(|task| !task.is_active),
+        TaskSort::Usage => {
+            let counts = db.record_counts_by_task()?;
+            tasks.sort_by_key(|task| {
+                Reverse(task.id.and_then(|id| counts.get(&id)).copied().unwrap_or(0))
+            });
+        }
+    }
 
     if !show_all {
         tasks = tasks
@@ -22,5 +51,10 @@ pub fn run(
     }
 
     writeln!(writer, "{}", table::task_list(&tasks))?;
+    if show_all {
+        writeln!(writer, "{} total", total_count)?;
+    } else {
+        writeln!(writer, "{} active / {} total", tasks.len(), total_count)?;
+    }
     Ok(())
 }
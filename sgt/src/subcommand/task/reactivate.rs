@@ -0,0 +1,30 @@
+use std::error::Error;
+use std::io::Write;
+
+use shigotolog::repository::Manipulation;
+use shigotolog::sqlite_db::SQLiteDatabase;
+
+use crate::prompt;
+use crate::util::map_tasks;
+
+pub fn run(db: &SQLiteDatabase, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let tasks = db
+        .tasks()?
+        .into_iter()
+        .filter(|task| !task.is_active)
+        .collect::<Vec<_>>();
+    if tasks.is_empty() {
+        writeln!(writer, "No inactive tasks.")?;
+        return Ok(());
+    }
+
+    let (mut task_map, keys) = map_tasks(tasks, true);
+    if let Ok(key) = prompt::select(keys, "Select task") {
+        let task = task_map.get_mut(&key).unwrap();
+        if let Ok(true) = prompt::confirm("Reactivate?", true) {
+            task.is_active = true;
+            db.register_task(task)?;
+        }
+    }
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use std::error::Error;
+use std::io::Write;
 
 use shigotolog::repository::Manipulation;
 use shigotolog::sqlite_db::SQLiteDatabase;
@@ -7,9 +8,9 @@ use shigotolog::task::Task;
 use crate::prompt;
 use crate::util::{map_tasks, push_front};
 
-pub fn run(db: &SQLiteDatabase) -> Result<(), Box<dyn Error>> {
+pub fn run(db: &SQLiteDatabase, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
     let tasks = db.tasks()?;
-    let (mut task_map, keys) = map_tasks(tasks);
+    let (mut task_map, keys) = map_tasks(tasks, false);
 
     let candidates = push_front("new".to_string(), keys);
     task_map.insert(candidates[0].clone(), Task::default());
@@ -43,11 +44,21 @@ pub fn run(db: &SQLiteDatabase) -> Result<(), Box<dyn Error>> {
             _ => panic!("Error"),
         }
 
+        match prompt::confirm("Billable?", task.is_billable) {
+            Ok(state) => task.is_billable = state,
+            _ => panic!("Error"),
+        }
+
         match prompt::confirm("Active task?", task.is_active) {
             Ok(state) => task.is_active = state,
             _ => panic!("Error"),
         }
 
+        if task.is_empty() {
+            writeln!(writer, "Empty task is not registered.")?;
+            return Ok(());
+        }
+
         db.register_task(task)
     } else {
         Ok(())
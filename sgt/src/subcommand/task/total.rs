@@ -0,0 +1,47 @@
+use std::error::Error;
+use std::io::Write;
+
+use chrono::TimeDelta;
+
+use shigotolog::datetime::TimeDisplay;
+use shigotolog::repository::Manipulation;
+use shigotolog::sqlite_db::SQLiteDatabase;
+
+use crate::prompt;
+use crate::util::map_tasks;
+
+pub fn run(db: &SQLiteDatabase, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let tasks = db.tasks()?;
+    let (task_map, keys) = map_tasks(tasks, true);
+    if let Ok(key) = prompt::select(keys, "Select task") {
+        let task = task_map.get(&key).unwrap();
+        let records = db.records_for_task(task.id.unwrap())?;
+
+        // records left open on past days are not counted
+        let total = records
+            .iter()
+            .filter_map(|record| {
+                record
+                    .duration_or_none()
+                    .or_else(|| record.is_running().then(|| record.duration()))
+            })
+            .sum::<TimeDelta>();
+        write!(
+            writer,
+            "{}: {} in {} records",
+            key,
+            total.to_string_hm(),
+            records.len()
+        )?;
+        if let (Some(first), Some(last)) = (records.first(), records.last()) {
+            write!(
+                writer,
+                " ({} - {})",
+                first.working_date.to_string_display(),
+                last.working_date.to_string_display()
+            )?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
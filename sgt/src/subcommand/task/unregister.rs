@@ -1,18 +1,38 @@
 use std::error::Error;
+use std::io::{stderr, Write};
 
 use shigotolog::repository::Manipulation;
 use shigotolog::sqlite_db::SQLiteDatabase;
 
 use crate::prompt;
-use crate::util::map_tasks;
+use crate::util::{map_tasks, NO_TASKS};
 
-pub fn run(db: &SQLiteDatabase) -> Result<(), Box<dyn Error>> {
+/// Unregisters a task. With `hide_records`, its records are also hidden from record lists
+/// at once, for tasks logged by mistake; they are kept in the database.
+pub fn run(
+    db: &SQLiteDatabase,
+    hide_records: bool,
+    mut writer: impl Write,
+) -> Result<(), Box<dyn Error>> {
     let tasks = db.tasks()?;
-    let (mut task_map, keys) = map_tasks(tasks);
+    let (mut task_map, keys) = map_tasks(tasks, true);
+    if keys.is_empty() {
+        writeln!(stderr(), "{}", NO_TASKS)?;
+        return Ok(());
+    }
     if let Ok(key) = prompt::select(keys, "Select task") {
         let task = task_map.get_mut(&key).unwrap();
-        if let Ok(false) = prompt::confirm("Unregister?", false) {
-            db.unregister_task(task.id.unwrap())?;
+        let id = task.id.unwrap();
+        if let Ok(true) = prompt::confirm("Unregister?", false) {
+            if hide_records {
+                let count = db.transaction(|db| {
+                    db.unregister_task(id)?;
+                    db.hide_records_for_task(id)
+                })?;
+                writeln!(writer, "Hidden {} records.", count)?;
+            } else {
+                db.unregister_task(id)?;
+            }
         }
     }
     Ok(())
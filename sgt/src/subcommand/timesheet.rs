@@ -0,0 +1,17 @@
+use std::error::Error;
+use std::io::Write;
+
+use shigotolog::datetime::WorkingDate;
+use shigotolog::repository::Manipulation;
+use shigotolog::sqlite_db::SQLiteDatabase;
+
+use crate::table;
+
+/// Prints the timesheet of the month, one row per day worked and a total row.
+pub fn run(db: &SQLiteDatabase, month: &str, mut writer: impl Write) -> Result<(), Box<dyn Error>> {
+    let (st, en) = WorkingDate::parse_ym(month)?;
+    let records = db.get_records_in_period(&st, &en)?;
+
+    writeln!(writer, "{}", table::timesheet(&records))?;
+    Ok(())
+}
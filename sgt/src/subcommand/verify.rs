@@ -0,0 +1,18 @@
+use std::error::Error;
+use std::io::Write;
+
+use shigotolog::datetime::WorkingDate;
+use shigotolog::repository::Manipulation;
+use shigotolog::sqlite_db::SQLiteDatabase;
+use shigotolog::verify::find_issues;
+
+use crate::table;
+
+/// Prints anomalies in the records. Returns `false` if any issue other than warnings is found.
+pub fn run(db: &SQLiteDatabase, mut writer: impl Write) -> Result<bool, Box<dyn Error>> {
+    let records = db.records()?;
+    let issues = find_issues(&records, &WorkingDate::today());
+
+    writeln!(writer, "{}", table::issue_list(&issues))?;
+    Ok(issues.iter().all(|issue| issue.anomaly.is_warning()))
+}
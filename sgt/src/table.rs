@@ -1,12 +1,177 @@
+use std::sync::OnceLock;
+
+use chrono::TimeDelta;
+use clap::ValueEnum;
+use regex::{Captures, Regex};
 use tabled::settings::location::ByColumnName;
-use tabled::settings::object::Rows;
+use tabled::settings::object::{Cell, Rows};
+use tabled::settings::peaker::PriorityMax;
 use tabled::settings::style::Style;
 use tabled::settings::themes::Colorization;
-use tabled::settings::{Alignment, Color, Modify};
+use tabled::settings::{Alignment, Color, Format, Modify, Remove, Width};
 use tabled::{Table, Tabled};
 
-use shigotolog::datetime::{TaskTime, TimeDisplay};
-use shigotolog::task::{Task, TaskRecord, TaskSummary};
+use shigotolog::datetime::{TaskTime, TimeDisplay, WorkingDate};
+use shigotolog::task::{self, percentages, Task, TaskRecord, TaskSummary};
+use shigotolog::verify::Issue;
+
+/// Border style of tables.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum TableStyle {
+    /// Unicode box-drawing borders
+    #[default]
+    Sharp,
+    /// ASCII borders
+    Ascii,
+    /// No borders
+    Plain,
+    /// Markdown table
+    Markdown,
+}
+
+/// Language of table headers and labels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Language {
+    /// English
+    #[default]
+    En,
+    /// Japanese
+    Ja,
+}
+
+static TABLE_STYLE: OnceLock<TableStyle> = OnceLock::new();
+
+/// Sets the table style used for the rest of the process.
+pub fn set_table_style(style: TableStyle) {
+    let _ = TABLE_STYLE.set(style);
+}
+
+static COLOR_ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// Enables or disables colors in tables for the rest of the process.
+pub fn set_color(enabled: bool) {
+    let _ = COLOR_ENABLED.set(enabled);
+}
+
+static MAX_WIDTH: OnceLock<Option<usize>> = OnceLock::new();
+
+/// Sets the maximum table width used for the rest of the process.
+pub fn set_max_width(width: Option<usize>) {
+    let _ = MAX_WIDTH.set(width);
+}
+
+static TICKET_URL_TEMPLATE: OnceLock<String> = OnceLock::new();
+
+/// Sets the URL template of ticket ids for the rest of the process.
+///
+/// `{id}` in the template is replaced with a ticket id (e.g. `ABC-123`) found in tables,
+/// such as in task names, descriptions and tags, which is then printed as a terminal hyperlink.
+pub fn set_ticket_url_template(template: String) {
+    let _ = TICKET_URL_TEMPLATE.set(template);
+}
+
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+/// Sets the language of tables used for the rest of the process.
+pub fn set_language(language: Language) {
+    let _ = LANGUAGE.set(language);
+}
+
+/// Translates an English header or label into the language of tables.
+///
+/// Text not in the catalog is returned as is.
+fn translate(text: &str) -> &str {
+    match LANGUAGE.get().copied().unwrap_or_default() {
+        Language::En => text,
+        Language::Ja => match text.trim_end() {
+            "(inactive)" => "(無効)",
+            "Active" => "有効",
+            "Average" => "平均",
+            "Bar" => "グラフ",
+            "Begin" => "開始",
+            "Billable" => "請求対象",
+            "Break" | "Break time" => "休憩",
+            "Clock-in" => "出勤",
+            "Clock-out" => "退勤",
+            "Date" => "日付",
+            "Days" => "日数",
+            "Description" => "説明",
+            "Duration" => "時間",
+            "error" => "エラー",
+            "End" => "終了",
+            "Issue" => "問題",
+            "Level 1" => "レベル1",
+            "Level 2" => "レベル2",
+            "Level 3" => "レベル3",
+            "Net" => "実働",
+            "No" => "いいえ",
+            "Note" => "メモ",
+            "Segments" => "区間",
+            "Severity" => "重大度",
+            "Sessions" => "セッション",
+            "Span" => "拘束時間",
+            "Tag" | "Tags" => "タグ",
+            "Task" => "タスク",
+            "Time" => "時刻",
+            "Total" => "合計",
+            "Unlogged" => "未記録",
+            "Weekday" => "曜日",
+            "warning" => "警告",
+            "Yes" => "はい",
+            _ => text,
+        },
+    }
+}
+
+fn style() -> TableStyle {
+    TABLE_STYLE.get().copied().unwrap_or_default()
+}
+
+fn color_enabled() -> bool {
+    COLOR_ENABLED.get().copied().unwrap_or(true)
+}
+
+fn max_width() -> Option<usize> {
+    MAX_WIDTH.get().copied().flatten()
+}
+
+/// Colors assigned to task names.
+const TASK_PALETTE: [Color; 6] = [
+    Color::FG_CYAN,
+    Color::FG_GREEN,
+    Color::FG_YELLOW,
+    Color::FG_BLUE,
+    Color::FG_MAGENTA,
+    Color::FG_RED,
+];
+
+/// Picks a color for a task name, stable across runs.
+fn task_color(name: &str) -> Color {
+    let hash = name
+        .bytes()
+        .fold(0u32, |h, b| h.wrapping_mul(31).wrapping_add(b as u32));
+    TASK_PALETTE[hash as usize % TASK_PALETTE.len()].clone()
+}
+
+/// Wraps ticket ids in the text with OSC 8 hyperlinks if a URL template is set.
+///
+/// Ids cut off by truncation are left as is.
+fn link_tickets(text: String) -> String {
+    let Some(template) = TICKET_URL_TEMPLATE.get() else {
+        return text;
+    };
+    let ticket_re = Regex::new(r"\b[A-Z][A-Z0-9]+-[0-9]+\b(…)?").unwrap();
+    ticket_re
+        .replace_all(&text, |captures: &Captures| {
+            let id = &captures[0];
+            if captures.get(1).is_some() {
+                return id.to_string();
+            }
+            let url = template.replace("{id}", id);
+            format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, id)
+        })
+        .into_owned()
+}
 
 /// Basic function that creates a list table
 fn build_table<I, T>(rows: I) -> Table
@@ -14,10 +179,37 @@ where
     I: IntoIterator<Item = T>,
     T: Tabled,
 {
-    Table::new(rows)
-        .with(Style::sharp())
-        .with(Colorization::exact([Color::BOLD], Rows::first()))
-        .to_owned()
+    let mut table = Table::new(rows);
+    let style = style();
+    match style {
+        TableStyle::Sharp => table.with(Style::sharp()),
+        TableStyle::Ascii => table.with(Style::ascii()),
+        TableStyle::Plain => table.with(Style::blank()),
+        TableStyle::Markdown => table.with(Style::markdown()),
+    };
+    // no escape sequences for plain and markdown so that the output can be pasted as is
+    if color_enabled() && matches!(style, TableStyle::Sharp | TableStyle::Ascii) {
+        table.with(Colorization::exact([Color::BOLD], Rows::first()));
+    }
+    table
+}
+
+/// Renders a table within the maximum width.
+///
+/// Headers are translated and truncation is applied last because columns are looked up by their
+/// header names.
+/// Hyperlinks are added to the rendered text since they are not counted as zero width.
+fn render(table: &mut Table) -> String {
+    table.with(Modify::new(Rows::first()).with(Format::content(|header| translate(header).into())));
+    // shrink the widest columns first so that short columns like times stay intact
+    if let Some(width) = max_width() {
+        table.with(
+            Width::truncate(width)
+                .suffix("…")
+                .priority(PriorityMax::right()),
+        );
+    }
+    link_tickets(table.to_string())
 }
 
 /// Task list table row.
@@ -34,6 +226,9 @@ struct TaskRow {
     #[tabled(rename = "Break time")]
     #[tabled(display_with = "display_bool")]
     is_break: bool,
+    #[tabled(rename = "Billable")]
+    #[tabled(display_with = "display_bool")]
+    is_billable: bool,
     #[tabled(rename = "Active")]
     #[tabled(display_with = "display_bool")]
     is_active: bool,
@@ -51,6 +246,7 @@ impl From<&Task> for TaskRow {
             level3,
             description: value.description.clone(),
             is_break: value.is_break,
+            is_billable: value.is_billable,
             is_active: value.is_active,
         }
     }
@@ -59,20 +255,22 @@ impl From<&Task> for TaskRow {
 /// Table output for `bool` value
 fn display_bool(x: &bool) -> String {
     match x {
-        true => "Yes".into(),
-        false => "No".into(),
+        true => translate("Yes").into(),
+        false => translate("No").into(),
     }
 }
 
 /// Creates a task list table.
 pub fn task_list(tasks: &[Task]) -> String {
     let rows = tasks.iter().map(TaskRow::from);
-    build_table(rows).to_string()
+    render(&mut build_table(rows))
 }
 
 /// Task records table row.
 #[derive(Tabled)]
 struct TaskRecordRow {
+    #[tabled(rename = "Id")]
+    id: String,
     #[tabled(rename = "Date")]
     date: String,
     #[tabled(rename = "Begin")]
@@ -83,35 +281,188 @@ struct TaskRecordRow {
     duration: String,
     #[tabled(rename = "Task")]
     task: String,
+    #[tabled(rename = "Tags")]
+    tags: String,
+    #[tabled(rename = "Note")]
+    note: String,
 }
 
 impl From<&TaskRecord> for TaskRecordRow {
     fn from(value: &TaskRecord) -> Self {
+        Self::new(value, None, false)
+    }
+}
+
+impl TaskRecordRow {
+    /// Creates a row showing the first `depth` levels of the task name, with times as stored
+    /// (`YYYY-MM-DDTHH:MM:SS`) if `raw` is set.
+    fn new(value: &TaskRecord, depth: Option<usize>, raw: bool) -> Self {
         let date = &value.working_date;
         let begin = &value.begin;
         let end = &value.end.as_ref();
-        let duration = &end.map_or_else(|| &TaskTime::now() - begin, |end| end - begin);
+
+        // flag records not ended: running today, or left open on a past day,
+        // whose duration is unknown
+        let (marker, duration) = match end {
+            Some(end) => ("", Some(*end - begin)),
+            None if value.is_running() => ("▶ ", Some(value.duration())),
+            None => ("! ", None),
+        };
 
         Self {
-            date: date.to_string(),
-            begin: begin.to_string_hm(),
-            end: end.map(|end| end.to_string_hm()).unwrap_or("".into()),
-            duration: duration.to_string_hm(),
-            task: value.task.format_name("/"),
+            id: value.id.map_or("".into(), |id| id.to_string()),
+            date: date.to_string_display(),
+            begin: format_time(begin, raw),
+            end: end.map(|end| format_time(end, raw)).unwrap_or("".into()),
+            duration: duration.map_or("".into(), |d| d.to_string_hm()),
+            task: match value.task.is_active {
+                true => format!("{}{}", marker, value.task.format_name_depth("/", depth)),
+                false => format!(
+                    "{}{} {}",
+                    marker,
+                    value.task.format_name_depth("/", depth),
+                    translate("(inactive)")
+                ),
+            },
+            tags: value.tags.join(", "),
+            note: value.note.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Creates a pseudo-row for unlogged time between `begin` and `end`.
+    fn gap(date: &WorkingDate, begin: &TaskTime, end: &TaskTime, raw: bool) -> Self {
+        Self {
+            id: "".into(),
+            date: date.to_string_display(),
+            begin: format_time(begin, raw),
+            end: format_time(end, raw),
+            duration: (end - begin).to_string_hm(),
+            task: "(gap)".into(),
+            tags: "".into(),
+            note: "".into(),
         }
     }
 }
 
+/// Formats a time of a record for display, or in full if `raw` is set.
+fn format_time(time: &TaskTime, raw: bool) -> String {
+    match raw {
+        true => time.to_string(),
+        false => time.to_string_display(),
+    }
+}
+
+/// Returns the unlogged span between two adjacent records of the same day, in either order.
+fn gap_between<'a>(a: &'a TaskRecord, b: &'a TaskRecord) -> Option<(&'a TaskTime, &'a TaskTime)> {
+    if a.working_date != b.working_date {
+        return None;
+    }
+    let (earlier, later) = if a.begin <= b.begin { (a, b) } else { (b, a) };
+    match &earlier.end {
+        Some(end) if end < &later.begin => Some((end, &later.begin)),
+        _ => None,
+    }
+}
+
 /// Creates task records table.
 pub fn record_list(records: &[TaskRecord]) -> String {
+    record_list_with_options(records, false, None, false, false)
+}
+
+/// Creates task records table, with record ids in the first column if `show_id` is set
+/// and task names cut to the first `depth` levels. If `show_gaps` is set, "(gap)" rows are
+/// inserted where no record covers the time between adjacent records. If `raw` is set,
+/// begin and end times are shown as stored (`YYYY-MM-DDTHH:MM:SS`).
+pub fn record_list_with_options(
+    records: &[TaskRecord],
+    show_id: bool,
+    depth: Option<usize>,
+    show_gaps: bool,
+    raw: bool,
+) -> String {
     if records.is_empty() {
         return "No Records".into();
     }
 
-    let rows = records.iter().map(TaskRecordRow::from);
-    build_table(rows)
-        .with(Modify::new(ByColumnName::new("Duration")).with(Alignment::right()))
-        .to_string()
+    // gap rows have no task to color
+    let mut rows = Vec::with_capacity(records.len());
+    for (i, record) in records.iter().enumerate() {
+        if show_gaps && i > 0 {
+            if let Some((begin, end)) = gap_between(&records[i - 1], record) {
+                rows.push((
+                    TaskRecordRow::gap(&record.working_date, begin, end, raw),
+                    None,
+                ));
+            }
+        }
+        rows.push((TaskRecordRow::new(record, depth, raw), Some(record)));
+    }
+
+    let (mut rows, sources): (Vec<_>, Vec<_>) = rows.into_iter().unzip();
+    let no_tags = records.iter().all(|record| record.tags.is_empty());
+    let build = |rows: &[TaskRecordRow]| {
+        let mut table = build_table(rows);
+        if show_id {
+            table.with(Modify::new(ByColumnName::new("Id")).with(Alignment::right()));
+        } else {
+            table.with(Remove::column(ByColumnName::new("Id")));
+        }
+        if no_tags {
+            table.with(Remove::column(ByColumnName::new("Tags")));
+        }
+        table
+    };
+
+    let mut table = build(&rows);
+    // on narrow terminals, begin and end times share a column, and the date is left out
+    // if it is the same for all, before cells are truncated
+    let too_wide = |table: &Table| max_width().is_some_and(|width| table.total_width() > width);
+    let compact = too_wide(&table);
+    let mut hide_date = false;
+    if compact {
+        for row in &mut rows {
+            row.begin = format!("{}-{}", row.begin, row.end);
+        }
+        table = build(&rows);
+        table.with(Remove::column(ByColumnName::new("End  "))).with(
+            Modify::new(Rows::first()).with(Format::content(|header| match header {
+                "Begin" => "Time".into(),
+                header => header.into(),
+            })),
+        );
+        let first_date = &records[0].working_date;
+        hide_date = too_wide(&table)
+            && records
+                .iter()
+                .all(|record| &record.working_date == first_date);
+        if hide_date {
+            table.with(Remove::column(ByColumnName::new("Date")));
+        }
+    }
+    table.with(Modify::new(ByColumnName::new("Duration")).with(Alignment::right()));
+
+    if color_enabled() && matches!(style(), TableStyle::Sharp | TableStyle::Ascii) {
+        // the Id column comes first
+        let task_column = TaskRecordRow::headers()
+            .iter()
+            .position(|header| header == "Task")
+            .unwrap()
+            - usize::from(!show_id)
+            - usize::from(compact)
+            - usize::from(hide_date);
+        for (i, source) in sources.iter().enumerate() {
+            match source {
+                Some(record) => {
+                    let color = task_color(&record.task.format_name_depth("/", depth));
+                    table.with(Modify::new(Cell::new(i + 1, task_column)).with(color));
+                }
+                None => {
+                    table.with(Modify::new(Rows::single(i + 1)).with(Color::FG_BRIGHT_BLACK));
+                }
+            }
+        }
+    }
+    render(&mut table)
 }
 
 /// Task summary table.
@@ -123,85 +474,300 @@ struct TotalDuration {
     end: String,
     #[tabled(rename = "Duration")]
     duration: String,
+    #[tabled(rename = "Unlogged")]
+    gap: String,
 }
 
 impl From<&TaskSummary> for TotalDuration {
     fn from(value: &TaskSummary) -> Self {
         Self {
-            begin: value.begin.to_string_hm(),
-            end: value.end.clone().map_or("".into(), |t| t.to_string_hm()),
+            begin: value.begin.to_string_display(),
+            end: value
+                .end
+                .clone()
+                .map_or("".into(), |t| t.to_string_display()),
             duration: value.total_duration.to_string_hm(),
+            gap: value.gap_duration.to_string_hm(),
         }
     }
 }
 
-/// Create task summary table.
-pub fn task_summary(records: &[TaskRecord]) -> String {
-    if records.is_empty() {
-        return "".into();
-    }
+/// Create task summary table. Break time within work records is deducted from the duration
+/// if `deduct_breaks` is set.
+pub fn task_summary(records: &[TaskRecord], deduct_breaks: bool) -> String {
+    let summary = match TaskSummary::new(records) {
+        Some(summary) if !summary.task_durations.is_empty() => [summary],
+        _ => return "".into(),
+    };
+
+    let total_duration = summary.iter().map(|summary| {
+        let mut row = TotalDuration::from(summary);
+        if deduct_breaks {
+            row.duration = summary.net_duration().to_string_hm();
+        }
+        row
+    });
+    render(
+        build_table(total_duration)
+            .with(Modify::new(ByColumnName::new("Duration")).with(Alignment::right()))
+            .with(Modify::new(ByColumnName::new("Unlogged")).with(Alignment::right())),
+    )
+}
+
+/// Duration by task table
+#[derive(Tabled)]
+pub struct TaskDuration {
+    #[tabled(rename = "Task")]
+    task: String,
+    #[tabled(rename = "Duration")]
+    duration: String,
+    #[tabled(rename = "%")]
+    percent: String,
+    #[tabled(rename = "Sessions")]
+    sessions: String,
+    #[tabled(rename = "Bar")]
+    bar: String,
+}
 
-    let summary = [TaskSummary::from(records)];
+/// Creates duration by task table. Percentages are shown with `precision` decimal places.
+pub fn task_durations(records: &[TaskRecord], precision: u32) -> String {
+    task_durations_with_depth(records, None, precision, None, None)
+}
 
-    if summary[0].task_durations.is_empty() {
+/// Draws a bar of `width` characters at most, proportional to `value / max`.
+///
+/// Eighth blocks are used for the fraction of a character.
+fn bar(value: TimeDelta, max: TimeDelta, width: usize) -> String {
+    const EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+    if max <= TimeDelta::zero() {
         return "".into();
     }
+    let eighths = (value.num_seconds() * width as i64 * 8 / max.num_seconds()).max(0) as usize;
+    let mut bar = "█".repeat(eighths / 8);
+    let rest = eighths % 8;
+    if rest > 0 {
+        bar.push(EIGHTHS[rest]);
+    }
+    bar
+}
 
-    let total_duration = summary.iter().map(TotalDuration::from);
-    build_table(total_duration)
+/// Creates duration by task table, grouping tasks by the first `depth` levels.
+///
+/// With `group_gap`, the number of sessions is also shown, counting records of the same task
+/// separated by at most the gap as one session. With `bar_width`, a bar chart of durations is
+/// drawn, the longest being the width.
+pub fn task_durations_with_depth(
+    records: &[TaskRecord],
+    depth: Option<usize>,
+    precision: u32,
+    group_gap: Option<TimeDelta>,
+    bar_width: Option<usize>,
+) -> String {
+    let summary = match TaskSummary::with_depth(records, depth) {
+        Some(summary) if !summary.task_durations.is_empty() => summary,
+        _ => return "".into(),
+    };
+
+    // sorted in descending order of duration
+    let durations = summary.sorted_task_durations();
+
+    let percents = percentages(
+        &durations.iter().map(|tup| tup.1).collect::<Vec<_>>(),
+        precision,
+    );
+
+    let session_counts = group_gap.map(|gap| task::session_counts(records, gap, depth));
+    // sorted in descending order, so the first is the longest
+    let max_duration = durations.first().map_or(TimeDelta::zero(), |tup| tup.1);
+
+    let task_durations = durations
+        .iter()
+        .zip(percents)
+        .map(|((task, duration), percent)| TaskDuration {
+            task: task.to_string(),
+            duration: duration.to_string_hm(),
+            percent: format!("{:.*}", precision as usize, percent),
+            sessions: session_counts
+                .as_ref()
+                .map_or("".into(), |counts| counts[*task].to_string()),
+            bar: bar_width.map_or("".into(), |width| bar(*duration, max_duration, width)),
+        })
+        .collect::<Vec<_>>();
+
+    let mut table = build_table(task_durations);
+    // remove unused columns first, as alignments are kept by column position
+    if group_gap.is_none() {
+        table.with(Remove::column(ByColumnName::new("Sessions")));
+    }
+    if bar_width.is_none() {
+        table.with(Remove::column(ByColumnName::new("Bar")));
+    }
+    table
         .with(Modify::new(ByColumnName::new("Duration")).with(Alignment::right()))
-        .to_string()
+        .with(Modify::new(ByColumnName::new("%")).with(Alignment::right()))
+        .with(Modify::new(ByColumnName::new("Sessions")).with(Alignment::right()));
+    render(&mut table)
 }
 
-/// Duration by task table
+/// Session list table row.
 #[derive(Tabled)]
-pub struct TaskDuration {
+struct SessionRow {
     #[tabled(rename = "Task")]
     task: String,
+    #[tabled(rename = "Segments")]
+    segments: usize,
     #[tabled(rename = "Duration")]
     duration: String,
-    #[tabled(rename = "%")]
-    percent: String,
 }
 
-/// Creates duration by task table.
-pub fn task_durations(records: &[TaskRecord]) -> String {
-    if records.is_empty() {
+/// Creates a table of sessions with the total duration across paused segments.
+pub fn session_list(records: &[TaskRecord]) -> String {
+    let Some(summary) = TaskSummary::new(records) else {
+        return "".into();
+    };
+
+    let mut sessions = summary.session_durations.iter().collect::<Vec<_>>();
+    if sessions.is_empty() {
         return "".into();
     }
+    sessions.sort();
+
+    let rows = sessions.into_iter().map(|(session_id, duration)| {
+        let segments = records
+            .iter()
+            .filter(|record| record.session_id == Some(*session_id))
+            .collect::<Vec<_>>();
+        SessionRow {
+            task: segments[0].task.format_name("/"),
+            segments: segments.len(),
+            duration: duration.to_string_hm(),
+        }
+    });
+    render(
+        build_table(rows)
+            .with(Modify::new(ByColumnName::new("Segments")).with(Alignment::right()))
+            .with(Modify::new(ByColumnName::new("Duration")).with(Alignment::right())),
+    )
+}
 
-    let summary = TaskSummary::from(records);
+/// Duration by tag table
+#[derive(Tabled)]
+pub struct TagDuration {
+    #[tabled(rename = "Tag")]
+    tag: String,
+    #[tabled(rename = "Duration")]
+    duration: String,
+    #[tabled(rename = "%")]
+    percent: String,
+}
 
-    if summary.task_durations.is_empty() {
+/// Creates duration by tag table. Percentages are shares of tagged time.
+pub fn tag_durations(records: &[TaskRecord], precision: u32) -> String {
+    let durations = task::tag_durations(records);
+    if durations.is_empty() {
         return "".into();
     }
 
-    let durations = summary.task_durations.iter().collect::<Vec<_>>();
-
-    let total_time = durations
-        .iter()
-        .map(|tup| *tup.1)
-        .reduce(|acc, dur| acc + dur)
-        .unwrap();
+    let percents = percentages(
+        &durations.iter().map(|tup| tup.1).collect::<Vec<_>>(),
+        precision,
+    );
 
-    let mut task_durations = durations
+    let tag_durations = durations
         .iter()
-        .map(|(task, duration)| TaskDuration {
-            task: task.to_string(),
+        .zip(percents)
+        .map(|((tag, duration), percent)| TagDuration {
+            tag: tag.clone(),
             duration: duration.to_string_hm(),
-            percent: format!(
-                "{:.1}",
-                duration.num_minutes() as f64 / total_time.num_minutes() as f64 * 100.
-            ),
+            percent: format!("{:.*}", precision as usize, percent),
         })
         .collect::<Vec<_>>();
-    // sort in descending order of duration
-    task_durations.sort_by(|a, b| b.duration.cmp(&a.duration));
 
-    build_table(task_durations)
-        .with(Modify::new(ByColumnName::new("Duration")).with(Alignment::right()))
-        .with(Modify::new(ByColumnName::new("%")).with(Alignment::right()))
-        .to_string()
+    render(
+        build_table(tag_durations)
+            .with(Modify::new(ByColumnName::new("Duration")).with(Alignment::right()))
+            .with(Modify::new(ByColumnName::new("%")).with(Alignment::right())),
+    )
+}
+
+/// Billable and non-billable duration table row.
+#[derive(Tabled)]
+struct BillableDuration {
+    #[tabled(rename = "Billable")]
+    #[tabled(display_with = "display_bool")]
+    is_billable: bool,
+    #[tabled(rename = "Duration")]
+    duration: String,
+    #[tabled(rename = "%")]
+    percent: String,
+}
+
+/// Creates a table splitting the total duration of work records into billable and non-billable.
+pub fn billable_durations(records: &[TaskRecord], precision: u32) -> String {
+    let summary = match TaskSummary::new(records) {
+        Some(summary) if !summary.task_durations.is_empty() => summary,
+        _ => return "".into(),
+    };
+    let durations = [
+        (true, summary.billable_duration),
+        (false, summary.total_duration - summary.billable_duration),
+    ];
+    let percents = percentages(&durations.map(|tup| tup.1), precision);
+
+    let rows = durations
+        .iter()
+        .zip(percents)
+        .map(|((is_billable, duration), percent)| BillableDuration {
+            is_billable: *is_billable,
+            duration: duration.to_string_hm(),
+            percent: format!("{:.*}", precision as usize, percent),
+        });
+    render(
+        build_table(rows)
+            .with(Modify::new(ByColumnName::new("Duration")).with(Alignment::right()))
+            .with(Modify::new(ByColumnName::new("%")).with(Alignment::right())),
+    )
+}
+
+/// Duration by day of the week table row.
+#[derive(Tabled)]
+struct WeekdayDuration {
+    #[tabled(rename = "Weekday")]
+    weekday: String,
+    #[tabled(rename = "Days")]
+    days: usize,
+    #[tabled(rename = "Total")]
+    total: String,
+    #[tabled(rename = "Average")]
+    average: String,
+}
+
+/// Creates a table of total and average durations by day of the week.
+///
+/// The average is over working dates with work records, so days off do not lower it.
+pub fn weekday_durations(records: &[TaskRecord]) -> String {
+    let durations = task::weekday_durations(records);
+    if durations.iter().all(|(_, _, days)| *days == 0) {
+        return "".into();
+    }
+
+    let rows = durations
+        .into_iter()
+        .map(|(weekday, total, days)| WeekdayDuration {
+            weekday: weekday.to_string(),
+            days,
+            total: total.to_string_hm(),
+            average: match days {
+                0 => "".into(),
+                _ => (total / days as i32).to_string_hm(),
+            },
+        });
+    render(
+        build_table(rows)
+            .with(Modify::new(ByColumnName::new("Days")).with(Alignment::right()))
+            .with(Modify::new(ByColumnName::new("Total")).with(Alignment::right()))
+            .with(Modify::new(ByColumnName::new("Average")).with(Alignment::right())),
+    )
 }
 
 /// Brwak time list table
@@ -215,27 +781,190 @@ pub struct BreakTimes {
 
 /// Creates break time list table.
 pub fn break_times(records: &[TaskRecord]) -> String {
-    if records.is_empty() {
-        return "".into();
-    }
-
-    let summary = TaskSummary::from(records);
-
-    if summary.break_times.is_empty() {
-        return "".into();
-    }
+    let summary = match TaskSummary::new(records) {
+        Some(summary) if !summary.break_times.is_empty() => summary,
+        _ => return "".into(),
+    };
 
     let break_times = summary.break_times.iter().map(|record| BreakTimes {
         task: record.task.format_name("/"),
         time: format!(
             "{} - {}",
-            record.begin.to_string_hm(),
+            record.begin.to_string_display(),
             &record
                 .end
                 .clone()
-                .map_or("".to_string(), |t| t.to_string_hm())
+                .map_or("".to_string(), |t| t.to_string_display())
         ),
     });
 
-    build_table(break_times).to_string()
+    format!(
+        "{}\nTotal break: {}",
+        render(&mut build_table(break_times)),
+        summary.break_duration.to_string_hm()
+    )
+}
+
+/// Attendance table row.
+#[derive(Tabled)]
+struct AttendanceRow {
+    #[tabled(rename = "Date")]
+    date: String,
+    #[tabled(rename = "Begin")]
+    begin: String,
+    #[tabled(rename = "End  ")]
+    end: String,
+    #[tabled(rename = "Span")]
+    span: String,
+}
+
+/// Creates attendance table with the first begin and the last end of work records by day.
+///
+/// `records` must be in chronological order. Days with only break records are skipped.
+pub fn attendance(records: &[TaskRecord]) -> String {
+    let rows = records
+        .chunk_by(|a, b| a.working_date == b.working_date)
+        .filter(|day| day.iter().any(|record| !record.is_break()))
+        .filter_map(|day| {
+            let summary = TaskSummary::new(day)?;
+            Some(AttendanceRow {
+                date: day[0].working_date.to_string_display(),
+                begin: summary.begin.to_string_display(),
+                end: summary
+                    .end
+                    .as_ref()
+                    .map_or("".into(), |t| t.to_string_display()),
+                span: summary
+                    .end
+                    .as_ref()
+                    .map_or("".into(), |end| (end - &summary.begin).to_string_hm()),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if rows.is_empty() {
+        return "No Records".into();
+    }
+
+    let days = rows.len();
+    format!(
+        "{}\nDays: {}",
+        render(
+            build_table(rows).with(Modify::new(ByColumnName::new("Span")).with(Alignment::right()))
+        ),
+        days
+    )
+}
+
+/// Timesheet table row.
+#[derive(Tabled)]
+struct TimesheetRow {
+    #[tabled(rename = "Date")]
+    date: String,
+    #[tabled(rename = "Clock-in")]
+    clock_in: String,
+    #[tabled(rename = "Clock-out")]
+    clock_out: String,
+    #[tabled(rename = "Break")]
+    break_time: String,
+    #[tabled(rename = "Net")]
+    net: String,
+}
+
+/// Creates a timesheet with clock-in, clock-out, break time and net worked time by day,
+/// followed by a total row.
+///
+/// `records` must be in chronological order. Days with only break records are skipped.
+pub fn timesheet(records: &[TaskRecord]) -> String {
+    let summaries = records
+        .chunk_by(|a, b| a.working_date == b.working_date)
+        .filter(|day| day.iter().any(|record| !record.is_break()))
+        .filter_map(|day| Some((&day[0].working_date, TaskSummary::new(day)?)))
+        .collect::<Vec<_>>();
+
+    if summaries.is_empty() {
+        return "No Records".into();
+    }
+
+    let mut total_break = TimeDelta::zero();
+    let mut total_net = TimeDelta::zero();
+    let mut rows = Vec::with_capacity(summaries.len() + 1);
+    for (date, summary) in &summaries {
+        total_break += summary.break_duration;
+        total_net += summary.net_duration();
+        rows.push(TimesheetRow {
+            date: date.to_string_display(),
+            clock_in: summary.begin.to_string_display(),
+            clock_out: summary
+                .end
+                .as_ref()
+                .map_or("".into(), |t| t.to_string_display()),
+            break_time: summary.break_duration.to_string_hm(),
+            net: summary.net_duration().to_string_hm(),
+        });
+    }
+    rows.push(TimesheetRow {
+        date: translate("Total").into(),
+        clock_in: "".into(),
+        clock_out: "".into(),
+        break_time: total_break.to_string_hm(),
+        net: total_net.to_string_hm(),
+    });
+
+    render(
+        build_table(rows)
+            .with(Modify::new(ByColumnName::new("Break")).with(Alignment::right()))
+            .with(Modify::new(ByColumnName::new("Net")).with(Alignment::right())),
+    )
+}
+
+/// Issue list table row.
+#[derive(Tabled)]
+struct IssueRow {
+    #[tabled(rename = "Id")]
+    id: String,
+    #[tabled(rename = "Date")]
+    date: String,
+    #[tabled(rename = "Time")]
+    time: String,
+    #[tabled(rename = "Task")]
+    task: String,
+    #[tabled(rename = "Severity")]
+    severity: String,
+    #[tabled(rename = "Issue")]
+    issue: String,
+}
+
+impl From<&Issue> for IssueRow {
+    fn from(value: &Issue) -> Self {
+        let record = &value.record;
+        Self {
+            id: record.id.map_or("".into(), |id| id.to_string()),
+            date: record.working_date.to_string_display(),
+            time: format!(
+                "{} - {}",
+                record.begin.to_string_display(),
+                record
+                    .end
+                    .clone()
+                    .map_or("".into(), |t| t.to_string_display())
+            ),
+            task: record.task.format_name("/"),
+            severity: match value.anomaly.is_warning() {
+                true => translate("warning").into(),
+                false => translate("error").into(),
+            },
+            issue: value.anomaly.to_string(),
+        }
+    }
+}
+
+/// Creates issue list table.
+pub fn issue_list(issues: &[Issue]) -> String {
+    if issues.is_empty() {
+        return "No Issues".into();
+    }
+
+    let rows = issues.iter().map(IssueRow::from);
+    render(build_table(rows).with(Modify::new(ByColumnName::new("Id")).with(Alignment::right())))
 }
@@ -1,17 +1,33 @@
 use std::collections::HashMap;
+use std::error::Error;
+use std::io::Write;
 
-use shigotolog::datetime::TimeDisplay;
+use shigotolog::datetime::{TaskTime, TimeDisplay, WorkingDate};
+use shigotolog::repository::Manipulation;
+use shigotolog::sqlite_db::SQLiteDatabase;
 use shigotolog::task::{Task, TaskRecord};
 
+/// Printed instead of an empty task picker.
+pub const NO_TASKS: &str = "No tasks registered; run `sgt task register`";
+
 /// Creates a map that is used as a lookup table for tasks.
 ///
 /// The key is a string that combines the task levels and description.
-pub fn map_tasks(tasks: Vec<Task>) -> (HashMap<String, Task>, Vec<String>) {
+/// Tasks without any level name are keyed by their id instead (e.g. `[id 3]`).
+/// Inactive tasks are skipped unless `include_inactive` is set.
+pub fn map_tasks(tasks: Vec<Task>, include_inactive: bool) -> (HashMap<String, Task>, Vec<String>) {
     let mut map = HashMap::new();
     let mut keys = vec![];
 
     for task in tasks {
-        let mut key = task.format_name("/");
+        if !include_inactive && !task.is_active {
+            continue;
+        }
+
+        let placeholder = task
+            .id
+            .map_or_else(|| "[no id]".to_string(), |id| format!("[id {}]", id));
+        let mut key = task.format_name_or("/", &placeholder);
         if !task.description.is_empty() {
             key += &format!(" - {}", &task.description)
         }
@@ -22,6 +38,41 @@ pub fn map_tasks(tasks: Vec<Task>) -> (HashMap<String, Task>, Vec<String>) {
     (map, keys)
 }
 
+/// Creates a lookup table of tasks to change the task of a record to, and the index of the
+/// record's current task in the keys.
+///
+/// The current task is a choice even if it is inactive, so that keeping it is the default.
+pub fn map_tasks_with_current(
+    tasks: Vec<Task>,
+    current: &Task,
+) -> (HashMap<String, Task>, Vec<String>, usize) {
+    let mut tasks = tasks
+        .into_iter()
+        .filter(|task| task.is_active || task.id == current.id)
+        .collect::<Vec<_>>();
+    if !tasks.iter().any(|task| task.id == current.id) {
+        tasks.push(current.clone());
+    }
+    let (map, keys) = map_tasks(tasks, true);
+    let cursor = keys
+        .iter()
+        .position(|key| map[key].id == current.id)
+        .unwrap_or_default();
+    (map, keys, cursor)
+}
+
+/// Formats `time` as a default of a time prompt on `date`.
+///
+/// Times from the end of the working date on are given with their date, so that
+/// `TaskTime::parse_with_date_or_datetime` reads them back as the same time.
+pub fn format_time_on(date: &WorkingDate, time: &TaskTime) -> String {
+    if time >= &date.end_of_day() {
+        time.format("%Y-%m-%d %H:%M")
+    } else {
+        time.to_string_hm()
+    }
+}
+
 pub fn push_front<T>(x: T, v: Vec<T>) -> Vec<T> {
     let mut result = vec![x];
     result.extend(v);
@@ -48,3 +99,76 @@ pub fn map_records(records: Vec<TaskRecord>) -> (HashMap<String, TaskRecord>, Ve
     }
     (map, keys)
 }
+
+/// Warns if a record before the specified date is left open.
+pub fn warn_open_record(
+    db: &SQLiteDatabase,
+    date: &WorkingDate,
+    mut writer: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(record) = db.latest_open_record()? {
+        if &record.working_date < date {
+            writeln!(
+                writer,
+                "Warning: {} started at {} on {} is not ended. \
+                Run `sgt end --date {2}` or `sgt fix --date {2}`.",
+                record.task.format_name("/"),
+                record.begin.to_string_hm(),
+                record.working_date,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_tasks_with_current() {
+        let active = Task::new(Some(1), Some("a"), None, None, "", false, true);
+        let inactive = Task::new(Some(2), Some("b"), None, None, "", false, false);
+        let other = Task::new(Some(3), Some("c"), None, None, "", false, false);
+        let tasks = vec![active.clone(), inactive.clone(), other];
+
+        let (map, keys, cursor) = map_tasks_with_current(tasks.clone(), &active);
+        assert_eq!(keys, vec!["a"]);
+        assert_eq!(map[&keys[cursor]], active);
+
+        // the inactive current task is kept as the default
+        let (map, keys, cursor) = map_tasks_with_current(tasks.clone(), &inactive);
+        assert_eq!(keys, vec!["a", "b"]);
+        assert_eq!(map[&keys[cursor]], inactive);
+
+        // the task of a record whose task is missing from the database
+        let orphan = Task::new(None, Some("d"), None, None, "", false, true);
+        let (map, keys, cursor) = map_tasks_with_current(tasks, &orphan);
+        assert_eq!(keys, vec!["a", "d"]);
+        assert_eq!(map[&keys[cursor]], orphan);
+    }
+
+    #[test]
+    fn test_format_time_on() -> Result<(), Box<dyn Error>> {
+        let date = WorkingDate::parse("2021-01-04")?;
+        let round_trip = |time: &TaskTime| {
+            TaskTime::parse_with_date_or_datetime(&date, &format_time_on(&date, time)).unwrap()
+        };
+
+        // a record from Monday 22:00 to Wednesday 07:00 is kept through an unchanged edit
+        let begin = TaskTime::parse("2021-01-04T22:00:00")?;
+        let end = TaskTime::parse("2021-01-06T07:00:00")?;
+        assert_eq!(format_time_on(&date, &begin), "22:00");
+        assert_eq!(format_time_on(&date, &end), "2021-01-06 07:00");
+        assert_eq!(round_trip(&begin), begin);
+        assert_eq!(round_trip(&end), end);
+
+        // times past midnight within the working date stay short
+        let late = TaskTime::parse("2021-01-05T04:59:00")?;
+        assert_eq!(format_time_on(&date, &late), "04:59");
+        assert_eq!(round_trip(&late), late);
+        let end_of_day = date.end_of_day();
+        assert_eq!(round_trip(&end_of_day), end_of_day);
+        Ok(())
+    }
+}